@@ -0,0 +1,103 @@
+//! Compares `try_from_str_migrations` on input that is already the latest version with
+//! deserializing the latest struct directly, and `try_from_str_migrations_concurrent`
+//! with `try_from_str_migrations` on a large input
+//!
+//! Run with `cargo bench`.
+
+use magic_migrate::TryMigrate;
+use serde::Deserialize;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct PersonV1 {
+    name: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct PersonV2 {
+    name: String,
+    title: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct PersonV3 {
+    name: String,
+    job_title: String,
+}
+
+impl From<PersonV1> for PersonV2 {
+    fn from(value: PersonV1) -> Self {
+        PersonV2 {
+            name: value.name,
+            title: None,
+        }
+    }
+}
+
+impl From<PersonV2> for PersonV3 {
+    fn from(value: PersonV2) -> Self {
+        PersonV3 {
+            name: value.name,
+            job_title: value.title.unwrap_or_default(),
+        }
+    }
+}
+
+magic_migrate::migrate_toml_chain!(PersonV1, PersonV2, PersonV3);
+
+const ITERATIONS: u32 = 100_000;
+const LARGE_ITERATIONS: u32 = 100;
+const INPUT: &str = "name = 'Schneems'\njob_title = 'Dev'";
+
+fn time(iterations: u32, f: impl Fn()) -> Duration {
+    let start = Instant::now();
+    for _ in 0..iterations {
+        f();
+    }
+    start.elapsed() / iterations
+}
+
+/// A `name` of `len` bytes followed by `fields`
+fn large(len: usize, fields: &str) -> String {
+    format!("name = '{}'\n{fields}", "x".repeat(len))
+}
+
+fn main() {
+    let direct = time(ITERATIONS, || {
+        let person = toml::from_str::<PersonV3>(black_box(INPUT)).unwrap();
+        black_box((person.name, person.job_title));
+    });
+    let migrated = time(ITERATIONS, || {
+        let person = PersonV3::try_from_str_migrations(black_box(INPUT))
+            .unwrap()
+            .unwrap();
+        black_box((person.name, person.job_title));
+    });
+
+    println!("toml::from_str                   {direct:?}/iter");
+    println!("try_from_str_migrations (latest) {migrated:?}/iter");
+
+    // The concurrent search parses the matching struct a second time to migrate it
+    for (version, fields) in [("latest", "job_title = 'Dev'"), ("oldest", "")] {
+        let input = large(1_000_000, fields);
+        let sequential = time(LARGE_ITERATIONS, || {
+            let person = PersonV3::try_from_str_migrations(black_box(&input))
+                .unwrap()
+                .unwrap();
+            black_box(person.name.len());
+        });
+        let concurrent = time(LARGE_ITERATIONS, || {
+            let person = PersonV3::try_from_str_migrations_concurrent(black_box(&input), 3)
+                .unwrap()
+                .unwrap();
+            black_box(person.name.len());
+        });
+
+        println!("try_from_str_migrations ({version}, 1MB)            {sequential:?}/iter");
+        println!("try_from_str_migrations_concurrent ({version}, 1MB) {concurrent:?}/iter");
+    }
+}
@@ -0,0 +1,87 @@
+use crate::walk::{self, Matched};
+use crate::TryMigrate;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+/// Runs many inputs through the chain of `T`, trying the struct that matched last time first
+///
+/// When most inputs in a batch are the same older version this skips the failed attempts on
+/// every newer struct. When the remembered struct does not match, every struct is tried as
+/// usual. Input that more than one struct can deserialize may be migrated from the
+/// remembered struct instead of the newest one, see
+/// [`TryMigrate::try_from_str_migrations_shadow`] for finding those.
+///
+/// ```rust
+/// use magic_migrate::{Adaptive, TryMigrate};
+#[doc = include_str!("fixtures/try_personV1_V2.txt")]
+///
+/// magic_migrate::try_migrate_toml_chain!(
+///     error: PersonMigrationError,
+///     chain: [PersonV1, PersonV2],
+/// );
+///
+/// let mut adaptive = Adaptive::<PersonV2>::new();
+/// assert_eq!(adaptive.last_version(), None);
+///
+/// for input in ["name = 'Schneems'\ntitle = 'Dev'", "name = 'Richard'\ntitle = 'Dev'"] {
+///     let person = adaptive.try_from_str_migrations(input).unwrap().unwrap();
+///     assert_eq!(person.job_title, "Dev".to_string());
+/// }
+/// assert_eq!(adaptive.last_version(), Some("PersonV1"));
+///
+/// let person = adaptive
+///     .try_from_str_migrations("name = 'Schneems'\njob_title = 'Dev'")
+///     .unwrap()
+///     .unwrap();
+/// assert_eq!(person.job_title, "Dev".to_string());
+/// assert_eq!(adaptive.last_version(), Some("PersonV2"));
+/// ```
+pub struct Adaptive<T> {
+    last: Option<Matched>,
+    latest: PhantomData<fn() -> T>,
+}
+
+impl<T: TryMigrate> Adaptive<T> {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            last: None,
+            latest: PhantomData,
+        }
+    }
+
+    /// Same as [`TryMigrate::try_from_str_migrations`] but tries the struct that matched
+    /// the previous input first
+    pub fn try_from_str_migrations(
+        &mut self,
+        input: &str,
+    ) -> Option<Result<T, <T as TryMigrate>::Error>> {
+        let remembered = self
+            .last
+            .and_then(|last| walk::walk_to::<T>(input, &|matched| matched.type_id == last.type_id));
+        let (matched, result) = remembered.or_else(|| walk::walk::<T>(input))?;
+        self.last = Some(matched);
+        Some(result)
+    }
+
+    /// The [`TryMigrate::version_name`] of the struct that matched the previous input
+    #[must_use]
+    pub fn last_version(&self) -> Option<&'static str> {
+        self.last.map(|matched| matched.name)
+    }
+}
+
+impl<T: TryMigrate> Default for Adaptive<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: TryMigrate> Debug for Adaptive<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Adaptive")
+            .field("latest", &T::version_name())
+            .field("last", &self.last_version())
+            .finish()
+    }
+}
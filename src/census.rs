@@ -0,0 +1,42 @@
+use std::fmt::Display;
+
+/// How many inputs deserialize into each struct in a chain, returned by
+/// [`crate::TryMigrate::version_census`]
+///
+/// Use it to find out when no stored data is left on an old version and it's safe to drop
+/// it from the chain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Census {
+    /// The [`crate::TryMigrate::version_name`] of every struct in the chain, newest first,
+    /// and how many inputs matched it
+    pub versions: Vec<(&'static str, usize)>,
+    /// Inputs that no struct in the chain could deserialize
+    pub unreadable: usize,
+}
+
+impl Census {
+    /// How many inputs matched the struct named `version`
+    #[must_use]
+    pub fn count(&self, version: &str) -> usize {
+        self.versions
+            .iter()
+            .find(|(name, _)| *name == version)
+            .map_or(0, |(_, count)| *count)
+    }
+
+    /// How many inputs were counted, including unreadable ones
+    #[must_use]
+    pub fn total(&self) -> usize {
+        self.versions.iter().map(|(_, count)| count).sum::<usize>() + self.unreadable
+    }
+}
+
+impl Display for Census {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (name, count) in &self.versions {
+            write!(f, "{count} on `{name}`, ")?;
+        }
+        write!(f, "{} unreadable", self.unreadable)
+    }
+}
@@ -0,0 +1,54 @@
+//! Checks that every struct in a chain agrees on how input is deserialized
+
+use crate::TryMigrate;
+use std::any::TypeId;
+use std::fmt::Display;
+
+/// A struct in the chain uses a different deserializer than the struct it migrates from
+///
+/// Returned by [`crate::TryMigrate::check_deserializers`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeserializerMismatch {
+    /// The [`crate::TryMigrate::version_name`] of the struct with the different deserializer
+    pub version: &'static str,
+    /// The deserializer type of the struct it migrates from
+    pub expected: &'static str,
+    /// The deserializer type of `version`
+    pub found: &'static str,
+}
+
+impl Display for DeserializerMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "`{}` deserializes with `{}` but the struct before it uses `{}`",
+            self.version, self.found, self.expected
+        )
+    }
+}
+
+impl std::error::Error for DeserializerMismatch {}
+
+/// The newest mismatch in the chain of `T`
+///
+/// This runs instead of failing compilation because [`TryMigrate::deserializer`] returns an
+/// `impl Deserializer`. Each impl's return type is a distinct opaque type, so there's no
+/// associated type to compare in a const assertion or trait bound. The concrete types are
+/// only known after monomorphization, when [`std::any::type_name`] can read them.
+pub(crate) fn check_deserializers<T: TryMigrate>() -> Result<(), DeserializerMismatch> {
+    if TypeId::of::<T>() == TypeId::of::<T::TryFrom>() {
+        return Ok(());
+    }
+
+    let found = crate::docs::deserializer_name::<T>();
+    let expected = crate::docs::deserializer_name::<T::TryFrom>();
+    if found != expected && !T::CHANGES_FORMAT {
+        return Err(DeserializerMismatch {
+            version: T::version_name(),
+            expected,
+            found,
+        });
+    }
+
+    check_deserializers::<T::TryFrom>()
+}
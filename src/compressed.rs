@@ -0,0 +1,56 @@
+use crate::TryMigrate;
+use std::marker::PhantomData;
+
+/// A compression format that payloads are decompressed with before they're migrated
+///
+/// Implement it with the decompression crate of your choice, such as `flate2` or `zstd`.
+pub trait Codec {
+    type Error;
+
+    fn decompress(input: &[u8]) -> Result<Vec<u8>, Self::Error>;
+}
+
+/// Decompress a payload with `C` then run it through the chain of `T` with
+/// [`TryMigrate::try_from_slice_migrations`]
+///
+/// ```rust
+/// use magic_migrate::{Codec, Compressed, TryMigrate};
+#[doc = include_str!("fixtures/try_personV1_V2.txt")]
+///
+/// magic_migrate::try_migrate_toml_chain!(
+///     error: PersonMigrationError,
+///     chain: [PersonV1, PersonV2],
+/// );
+///
+/// // Stands in for gzip or zstd
+/// struct Reversed;
+///
+/// impl Codec for Reversed {
+///     type Error = std::convert::Infallible;
+///
+///     fn decompress(input: &[u8]) -> Result<Vec<u8>, Self::Error> {
+///         Ok(input.iter().rev().copied().collect())
+///     }
+/// }
+///
+/// let compressed = "name = 'Schneems'\ntitle = 'Dev'".bytes().rev().collect::<Vec<_>>();
+/// let person = Compressed::<Reversed, PersonV2>::try_from_slice(&compressed)
+///     .unwrap()
+///     .unwrap()
+///     .unwrap();
+/// assert_eq!(person.job_title, "Dev".to_string());
+/// ```
+pub struct Compressed<C, T> {
+    types: PhantomData<fn() -> (C, T)>,
+}
+
+impl<C: Codec, T: TryMigrate> Compressed<C, T> {
+    /// Errors when the payload can't be decompressed, otherwise same as
+    /// [`TryMigrate::try_from_slice_migrations`]
+    pub fn try_from_slice(
+        input: &[u8],
+    ) -> Result<Option<Result<T, <T as TryMigrate>::Error>>, C::Error> {
+        let bytes = C::decompress(input)?;
+        Ok(T::try_from_slice_migrations(&bytes))
+    }
+}
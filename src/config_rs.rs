@@ -0,0 +1,132 @@
+//! Run values from the [`config`] crate through a migration chain
+
+use crate::{events, walk, TryMigrate};
+use config::{Config, ConfigError, Map, Source, Value};
+use serde::Serialize;
+use std::any::TypeId;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+/// Run a built [`Config`] through the migration chain of `T`
+///
+/// Same as [`TryMigrate::try_from_str_migrations`] but each struct in the chain is
+/// deserialized from the layered config values instead of a string.
+///
+/// ```rust
+/// use magic_migrate::TryMigrate;
+#[doc = include_str!("fixtures/try_personV1_V2.txt")]
+///
+/// magic_migrate::try_migrate_toml_chain!(
+///     error: PersonMigrationError,
+///     chain: [PersonV1, PersonV2],
+/// );
+///
+/// let config = config::Config::builder()
+///     .set_default("name", "Schneems").unwrap()
+///     .set_default("title", "Dev").unwrap()
+///     .build()
+///     .unwrap();
+///
+/// let person: PersonV2 = magic_migrate::try_from_config_migrations(&config)
+///     .unwrap()
+///     .unwrap();
+/// assert_eq!(person.job_title, "Dev".to_string());
+/// ```
+#[must_use]
+pub fn try_from_config_migrations<T: TryMigrate>(
+    config: &Config,
+) -> Option<Result<T, <T as TryMigrate>::Error>> {
+    let attempt = config.clone().try_deserialize::<T>();
+    events::attempt(T::version_name(), attempt.is_ok());
+    if let Ok(instance) = attempt {
+        Some(Ok(instance))
+    } else if TypeId::of::<T>() == TypeId::of::<T::TryFrom>() {
+        None
+    } else {
+        try_from_config_migrations::<T::TryFrom>(config)
+            .map(|inner| inner.map_err(Into::into).and_then(walk::step::<T>))
+    }
+}
+
+/// A [`Source`] that migrates the values of another source to the latest version of `T`
+///
+/// Use it to migrate a single layer (such as an old settings file) before it is merged
+/// with the other sources of a [`Config`].
+///
+/// ```rust
+/// use magic_migrate::TryMigrate;
+#[doc = include_str!("fixtures/try_personV1_V2.txt")]
+///
+/// magic_migrate::try_migrate_toml_chain!(
+///     error: PersonMigrationError,
+///     chain: [PersonV1, PersonV2],
+/// );
+///
+/// let file = config::File::from_str(
+///     "name = 'Schneems'\ntitle = 'Dev'",
+///     config::FileFormat::Toml
+/// );
+///
+/// let config = config::Config::builder()
+///     .add_source(magic_migrate::MigrateSource::<PersonV2, _>::new(file))
+///     .set_override("name", "Richard").unwrap()
+///     .build()
+///     .unwrap();
+///
+/// let person: PersonV2 = config.try_deserialize().unwrap();
+/// assert_eq!(person.name, "Richard".to_string());
+/// assert_eq!(person.job_title, "Dev".to_string());
+/// ```
+pub struct MigrateSource<T, S> {
+    source: S,
+    latest: PhantomData<fn() -> T>,
+}
+
+impl<T, S> MigrateSource<T, S> {
+    pub fn new(source: S) -> Self {
+        Self {
+            source,
+            latest: PhantomData,
+        }
+    }
+}
+
+impl<T: TryMigrate, S: Debug> Debug for MigrateSource<T, S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MigrateSource")
+            .field("latest", &T::version_name())
+            .field("source", &self.source)
+            .finish()
+    }
+}
+
+impl<T, S: Clone> Clone for MigrateSource<T, S> {
+    fn clone(&self) -> Self {
+        Self::new(self.source.clone())
+    }
+}
+
+impl<T, S> Source for MigrateSource<T, S>
+where
+    T: TryMigrate + Serialize,
+    S: Source + Clone + Send + Sync + 'static,
+{
+    fn clone_into_box(&self) -> Box<dyn Source + Send + Sync> {
+        Box::new(self.clone())
+    }
+
+    fn collect(&self) -> Result<Map<String, Value>, ConfigError> {
+        let config = Config::builder().add_source(self.source.clone()).build()?;
+        let latest = try_from_config_migrations::<T>(&config)
+            .ok_or_else(|| {
+                ConfigError::Message(format!(
+                    "No version in the chain of {} matches {:?}",
+                    T::version_name(),
+                    self.source
+                ))
+            })?
+            .map_err(|error| ConfigError::Message(error.to_string()))?;
+
+        Config::try_from(&latest)?.collect()
+    }
+}
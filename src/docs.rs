@@ -0,0 +1,71 @@
+//! Markdown documentation for a migration chain
+
+use crate::{Schema, TryMigrate};
+use std::any::TypeId;
+use std::fmt::Write;
+
+/// A markdown table with a row for each struct in the chain of `T`, oldest first
+pub(crate) fn chain_docs<T: TryMigrate>() -> String {
+    let mut out = String::from(
+        "| Version | Since | Added | Removed | Error | Deserializer |\n\
+         |---------|-------|-------|---------|-------|--------------|\n",
+    );
+    rows::<T>(&mut out);
+    out
+}
+
+/// Writes the rows for every struct before `T` then the row for `T`, returns the schema of `T`
+fn rows<T: TryMigrate>(out: &mut String) -> Schema {
+    let schema = T::schema();
+    let previous = if TypeId::of::<T>() == TypeId::of::<T::TryFrom>() {
+        Schema::Unit
+    } else {
+        rows::<T::TryFrom>(out)
+    };
+
+    let after = fields(&schema);
+    let before = fields(&previous);
+    let added = after.iter().filter(|field| !before.contains(field));
+    let removed = before.iter().filter(|field| !after.contains(field));
+
+    writeln!(
+        out,
+        "| `{}` | {} | {} | {} | `{}` | `{}` |",
+        T::version_name(),
+        T::since().unwrap_or_default(),
+        cell(added),
+        cell(removed),
+        crate::short_type_name::<<T as TryMigrate>::Error>(),
+        deserializer_name::<T>(),
+    )
+    .expect("Writing to a String cannot fail");
+
+    schema
+}
+
+fn fields(schema: &Schema) -> Vec<(&'static str, &Schema)> {
+    match schema {
+        Schema::Struct { fields, .. } => {
+            fields.iter().map(|(name, field)| (*name, field)).collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn cell<'a>(fields: impl Iterator<Item = &'a (&'static str, &'a Schema)>) -> String {
+    fields
+        .map(|(name, schema)| format!("`{name}: {schema}`"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The full path of the deserializer type of `T`, without generic parameters
+///
+/// Read from the return type of [`TryMigrate::deserializer`], no deserializer is made.
+pub(crate) fn deserializer_name<T: TryMigrate>() -> &'static str {
+    fn returned<R>(_: impl Fn(&'static str) -> R) -> &'static str {
+        let name = std::any::type_name::<R>();
+        &name[..name.find('<').unwrap_or(name.len())]
+    }
+    returned(T::deserializer)
+}
@@ -0,0 +1,138 @@
+use crate::MigrateError;
+use std::fmt::Debug;
+
+type Matcher<V> = Box<dyn Fn(&V) -> bool>;
+type Step<V, E> = Box<dyn Fn(V) -> Result<V, E>>;
+
+struct Version<V, E> {
+    name: &'static str,
+    matches: Matcher<V>,
+    /// Converts a value of the version before this one, `None` for the first version
+    step: Option<Step<V, E>>,
+}
+
+/// A migration chain that is assembled at runtime, for when the versions aren't known at
+/// compile time such as in a plugin host
+///
+/// Each version is a predicate that recognizes values of that version and a step that
+/// converts a value of the version before it. Like [`crate::TryMigrate`] chains the newest
+/// version that matches is picked and every step after it is run in order.
+///
+/// ```rust
+/// use magic_migrate::{DynamicChain, MigrateError};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize, Debug)]
+/// struct Person {
+///     name: String,
+///     job_title: String,
+/// }
+///
+/// let chain = DynamicChain::<toml::Value, String>::new("PersonV1", |value| value.get("name").is_some())
+///     .link(
+///         "PersonV2",
+///         |value| value.get("job_title").is_some(),
+///         |mut value| {
+///             let table = value.as_table_mut().ok_or("Not a table")?;
+///             let title = table.remove("title").ok_or("Title missing")?;
+///             table.insert("job_title".to_string(), title);
+///             Ok(value)
+///         },
+///     );
+///
+/// let value: toml::Value = toml::from_str("name = 'Schneems'\ntitle = 'Dev'").unwrap();
+/// let person: Person = chain
+///     .migrate_into(value, |value| value.try_into().map_err(|error: toml::de::Error| error.to_string()))
+///     .unwrap();
+/// assert_eq!(person.job_title, "Dev".to_string());
+///
+/// let value: toml::Value = toml::from_str("name = 'Schneems'").unwrap();
+/// let result = chain.migrate(value);
+/// assert!(matches!(result, Err(MigrateError::Migrate { version: "PersonV1", .. })));
+/// ```
+pub struct DynamicChain<V, E> {
+    versions: Vec<Version<V, E>>,
+}
+
+impl<V, E> DynamicChain<V, E> {
+    /// A chain with a single version, `matches` returns true for values of that version
+    pub fn new(name: &'static str, matches: impl Fn(&V) -> bool + 'static) -> Self {
+        Self {
+            versions: vec![Version {
+                name,
+                matches: Box::new(matches),
+                step: None,
+            }],
+        }
+    }
+
+    /// Add a newer version, `step` converts a value of the current latest version into it
+    #[must_use]
+    pub fn link(
+        mut self,
+        name: &'static str,
+        matches: impl Fn(&V) -> bool + 'static,
+        step: impl Fn(V) -> Result<V, E> + 'static,
+    ) -> Self {
+        self.versions.push(Version {
+            name,
+            matches: Box::new(matches),
+            step: Some(Box::new(step)),
+        });
+        self
+    }
+
+    /// The name of every version, oldest first
+    #[must_use]
+    pub fn versions(&self) -> Vec<&'static str> {
+        self.versions.iter().map(|version| version.name).collect()
+    }
+
+    /// Migrate `value` to the latest version
+    ///
+    /// The error names the version of the value that the failed step was given.
+    pub fn migrate(&self, value: V) -> Result<V, MigrateError<E>> {
+        let index = self
+            .versions
+            .iter()
+            .rposition(|version| (version.matches)(&value))
+            .ok_or(MigrateError::NoMatchingVersion)?;
+
+        self.versions
+            .windows(2)
+            .skip(index)
+            .try_fold(value, |value, pair| {
+                let step = pair[1]
+                    .step
+                    .as_ref()
+                    .expect("Only the first version has no step");
+                step(value).map_err(|error| MigrateError::Migrate {
+                    version: pair[0].name,
+                    error,
+                    help: None,
+                })
+            })
+    }
+
+    /// Migrate `value` to the latest version and convert it into `T` with `deserialize`
+    pub fn migrate_into<T>(
+        &self,
+        value: V,
+        deserialize: impl FnOnce(V) -> Result<T, E>,
+    ) -> Result<T, MigrateError<E>> {
+        let latest = self.migrate(value)?;
+        deserialize(latest).map_err(|error| MigrateError::Migrate {
+            version: self.versions[self.versions.len() - 1].name,
+            error,
+            help: None,
+        })
+    }
+}
+
+impl<V, E> Debug for DynamicChain<V, E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DynamicChain")
+            .field("versions", &self.versions())
+            .finish()
+    }
+}
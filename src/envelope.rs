@@ -0,0 +1,170 @@
+use crate::TryMigrate;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::any::TypeId;
+use std::fmt::Display;
+
+type Migrated<T> = Result<T, <T as TryMigrate>::Error>;
+
+/// A binary format such as `bincode` or `postcard`, used with an [`Envelope`]
+///
+/// Formats that aren't self-describing can deserialize bytes meant for a different struct
+/// without an error, so they can't be tried against each struct in a chain.
+pub trait BinaryFormat {
+    type Error;
+
+    fn serialize<T: Serialize>(value: &T) -> Result<Vec<u8>, Self::Error>;
+
+    fn deserialize<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, Self::Error>;
+}
+
+/// Frames binary payloads with magic bytes and the version of the struct they hold, so the
+/// right struct is deserialized directly on read and then migrated to the latest version
+///
+/// The header is the magic bytes followed by the struct's position in the chain, counting
+/// from the first struct, as a little-endian `u32`. Removing a struct from the front of a
+/// chain changes the position of every struct after it.
+///
+/// ```rust
+/// use magic_migrate::{BinaryFormat, Envelope, EnvelopeError, TryMigrate};
+#[doc = include_str!("fixtures/try_personV1_V2.txt")]
+///
+/// magic_migrate::try_migrate_toml_chain!(
+///     error: PersonMigrationError,
+///     chain: [PersonV1, PersonV2],
+/// );
+///
+/// // Stands in for bincode or postcard
+/// struct Bytes;
+///
+/// impl BinaryFormat for Bytes {
+///     type Error = String;
+///
+///     fn serialize<T: Serialize>(value: &T) -> Result<Vec<u8>, String> {
+///         toml::to_string(value).map(String::into_bytes).map_err(|e| e.to_string())
+///     }
+///
+///     fn deserialize<T: serde::de::DeserializeOwned>(bytes: &[u8]) -> Result<T, String> {
+///         let input = std::str::from_utf8(bytes).map_err(|e| e.to_string())?;
+///         toml::from_str(input).map_err(|e| e.to_string())
+///     }
+/// }
+///
+/// const ENVELOPE: Envelope = Envelope::new(b"PRSN");
+///
+/// let old = PersonV1 { name: "Schneems".to_string(), title: Some("Dev".to_string()) };
+/// let bytes = ENVELOPE.to_bytes::<Bytes, _>(&old).unwrap();
+/// assert!(bytes.starts_with(b"PRSN\0\0\0\0"));
+///
+/// let person: PersonV2 = ENVELOPE.from_bytes::<Bytes, _>(&bytes).unwrap().unwrap();
+/// assert_eq!(person.job_title, "Dev".to_string());
+///
+/// let result = ENVELOPE.from_bytes::<Bytes, PersonV2>(b"name = 'Schneems'");
+/// assert!(matches!(result, Err(EnvelopeError::MissingMagic)));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Envelope {
+    magic: &'static [u8],
+}
+
+impl Envelope {
+    #[must_use]
+    pub const fn new(magic: &'static [u8]) -> Self {
+        Self { magic }
+    }
+
+    /// Serialize `value` with `F` behind a header with its position in its chain
+    pub fn to_bytes<F, T>(&self, value: &T) -> Result<Vec<u8>, EnvelopeError<F::Error>>
+    where
+        F: BinaryFormat,
+        T: TryMigrate + Serialize,
+    {
+        let version =
+            u32::try_from(position::<T>()).map_err(|_| EnvelopeError::UnknownVersion(u32::MAX))?;
+        let payload = F::serialize(value).map_err(EnvelopeError::Format)?;
+
+        let mut bytes = Vec::with_capacity(self.magic.len() + 4 + payload.len());
+        bytes.extend_from_slice(self.magic);
+        bytes.extend_from_slice(&version.to_le_bytes());
+        bytes.extend_from_slice(&payload);
+        Ok(bytes)
+    }
+
+    /// Deserialize the struct named in the header with `F` then migrate it to `T`
+    pub fn from_bytes<F, T>(&self, input: &[u8]) -> Result<Migrated<T>, EnvelopeError<F::Error>>
+    where
+        F: BinaryFormat,
+        T: TryMigrate,
+    {
+        let rest = input
+            .strip_prefix(self.magic)
+            .ok_or(EnvelopeError::MissingMagic)?;
+        let (version, payload) = rest
+            .split_first_chunk::<4>()
+            .ok_or(EnvelopeError::MissingMagic)?;
+        let version = u32::from_le_bytes(*version);
+        let index = usize::try_from(version).map_err(|_| EnvelopeError::UnknownVersion(version))?;
+
+        from_position::<F, T>(payload, index)
+            .ok_or(EnvelopeError::UnknownVersion(version))?
+            .map_err(EnvelopeError::Format)
+    }
+}
+
+/// Errors from reading or writing an [`Envelope`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum EnvelopeError<E> {
+    /// The input does not start with the magic bytes and a version
+    MissingMagic,
+    /// The version in the header is not a struct in the chain
+    UnknownVersion(u32),
+    /// The binary format failed to serialize or deserialize the payload
+    Format(E),
+}
+
+impl<E: Display> Display for EnvelopeError<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EnvelopeError::MissingMagic => f.write_str("Input is missing the envelope header"),
+            EnvelopeError::UnknownVersion(version) => {
+                write!(f, "No struct in the chain has version {version}")
+            }
+            EnvelopeError::Format(error) => write!(f, "Could not read the payload: {error}"),
+        }
+    }
+}
+
+impl<E: Display + std::fmt::Debug> std::error::Error for EnvelopeError<E> {}
+
+/// Position of `T` in its chain, counting from the first struct
+fn position<T: TryMigrate>() -> usize {
+    if TypeId::of::<T>() == TypeId::of::<T::TryFrom>() {
+        0
+    } else {
+        position::<T::TryFrom>() + 1
+    }
+}
+
+/// Deserialize `payload` into the struct at `index` in the chain of `T` and migrate it to `T`,
+/// returns `None` when the chain is shorter than `index`
+fn from_position<F: BinaryFormat, T: TryMigrate>(
+    payload: &[u8],
+    index: usize,
+) -> Option<Result<Migrated<T>, F::Error>> {
+    if position::<T>() == index {
+        Some(F::deserialize::<T>(payload).map(Ok))
+    } else if TypeId::of::<T>() == TypeId::of::<T::TryFrom>() {
+        None
+    } else {
+        from_position::<F, T::TryFrom>(payload, index).map(|inner| {
+            inner.map(|result| {
+                result
+                    .map_err(Into::into)
+                    .and_then(|before: <T as TryMigrate>::TryFrom| {
+                        T::try_from(before).map_err(Into::into)
+                    })
+            })
+        })
+    }
+}
@@ -0,0 +1,378 @@
+use std::fmt::{Debug, Display, Write};
+use std::sync::Arc;
+
+/// Errors from running input through a migration chain
+///
+/// Returned by [`crate::TryMigrate::try_from_str_migrations_result`]. It implements
+/// [`std::error::Error`] when the chain's error does, so `?` converts it into
+/// `Box<dyn Error>`, `anyhow::Error`, or `eyre::Report`.
+///
+/// The alternate form (`{:#}`) puts the cause on its own indented line. The chain's
+/// error is formatted with `{:#}` too, so an error like `anyhow::Error` that includes its
+/// sources in the alternate form shows them. For other errors use [`MigrateError::report`].
+///
+/// ```rust
+/// use magic_migrate::TryMigrate;
+#[doc = include_str!("fixtures/try_personV1_V2.txt")]
+///
+/// magic_migrate::try_migrate_toml_chain!(
+///     error: PersonMigrationError,
+///     chain: [PersonV1, PersonV2],
+/// );
+///
+/// let error = PersonV2::try_from_str_migrations_result("name = 'Schneems'").unwrap_err();
+/// assert_eq!(
+///     format!("{error}"),
+///     "Could not migrate from `PersonV1`: Title cannot be empty!!!"
+/// );
+/// assert_eq!(
+///     format!("{error:#}"),
+///     "Could not migrate from `PersonV1`\n\nCaused by:\n    Title cannot be empty!!!"
+/// );
+/// ```
+///
+/// It's [`Clone`] when the chain's error is, otherwise use [`MigrateError::into_shared`].
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum MigrateError<E> {
+    /// No struct in the chain could deserialize the input
+    NoMatchingVersion,
+    /// The input is empty or only whitespace and was rejected by
+    /// [`crate::EmptyInputPolicy::Reject`]
+    EmptyInput,
+    /// The input deserialized into `version` but migrating it to the latest version failed
+    ///
+    /// `help` is the [`crate::TryMigrate::help`] link of `version`.
+    Migrate {
+        version: &'static str,
+        error: E,
+        help: Option<&'static str>,
+    },
+    /// None of the newest `limit` structs deserialize the input and the older ones were not
+    /// tried, returned by [`crate::TryMigrate::try_from_str_migrations_with_limit`]
+    TooOld { limit: usize },
+    /// The conversion into `version` panicked, returned by
+    /// [`crate::TryMigrate::try_from_str_migrations_catch_unwind`]
+    StepPanicked {
+        version: &'static str,
+        message: String,
+    },
+}
+
+/// What went wrong, without the details, for branching on errors from this crate
+///
+/// Returned by the `kind()` method of [`MigrateError`], `LoadError`,
+/// [`crate::StoreError`], and `SectionError`.
+///
+/// ```rust
+/// use magic_migrate::{MigrateErrorKind, TryMigrate};
+#[doc = include_str!("fixtures/try_personV1_V2.txt")]
+///
+/// magic_migrate::try_migrate_toml_chain!(
+///     error: PersonMigrationError,
+///     chain: [PersonV1, PersonV2],
+/// );
+///
+/// let error = PersonV2::try_from_str_migrations_result("name = 'Schneems'").unwrap_err();
+/// assert_eq!(error.kind(), MigrateErrorKind::Migrate);
+///
+/// let error = PersonV2::try_from_str_migrations_result("").unwrap_err();
+/// assert_eq!(error.kind(), MigrateErrorKind::NoMatchingVersion);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum MigrateErrorKind {
+    /// No struct in the chain could deserialize the input
+    NoMatchingVersion,
+    /// The input is empty or only whitespace
+    EmptyInput,
+    /// A struct deserialized but migrating it to the latest version failed
+    Migrate,
+    /// The input could only be an older version than the caller accepts
+    TooOld,
+    /// A conversion between two structs in the chain panicked
+    StepPanicked,
+    /// Reading or writing a file or storage backend failed
+    Io,
+    /// The input could not be read as the expected format, for example it's not UTF-8
+    Format,
+    /// The latest version could not be serialized
+    Serialize,
+}
+
+impl<E> MigrateError<E> {
+    #[must_use]
+    pub fn kind(&self) -> MigrateErrorKind {
+        match self {
+            MigrateError::NoMatchingVersion => MigrateErrorKind::NoMatchingVersion,
+            MigrateError::EmptyInput => MigrateErrorKind::EmptyInput,
+            MigrateError::Migrate { .. } => MigrateErrorKind::Migrate,
+            MigrateError::TooOld { .. } => MigrateErrorKind::TooOld,
+            MigrateError::StepPanicked { .. } => MigrateErrorKind::StepPanicked,
+        }
+    }
+
+    /// Moves the chain's error into an [`Arc`] so the error can be cloned, such as to
+    /// cache a failure or retry later, even when the chain's error isn't [`Clone`]
+    ///
+    /// ```rust
+    /// use magic_migrate::{MigrateError, TryMigrate};
+    #[doc = include_str!("fixtures/try_personV1_V2.txt")]
+    ///
+    /// magic_migrate::try_migrate_toml_chain!(
+    ///     error: PersonMigrationError,
+    ///     chain: [PersonV1, PersonV2],
+    /// );
+    ///
+    /// let error = PersonV2::try_from_str_migrations_result("name = 'Schneems'")
+    ///     .unwrap_err()
+    ///     .into_shared();
+    /// let cached = error.clone();
+    /// assert_eq!(cached.to_string(), error.to_string());
+    /// assert!(matches!(cached, MigrateError::Migrate { version: "PersonV1", .. }));
+    /// ```
+    #[must_use]
+    pub fn into_shared(self) -> MigrateError<Arc<E>> {
+        self.map_error(Arc::new)
+    }
+
+    /// The link to remediation docs for the struct that failed to migrate, see
+    /// [`crate::TryMigrate::help`]
+    #[must_use]
+    pub fn help(&self) -> Option<&'static str> {
+        match self {
+            MigrateError::Migrate { help, .. } => *help,
+            _ => None,
+        }
+    }
+
+    /// Converts the chain's error, keeping the rest of the variant
+    pub(crate) fn map_error<F>(self, f: impl FnOnce(E) -> F) -> MigrateError<F> {
+        match self {
+            MigrateError::NoMatchingVersion => MigrateError::NoMatchingVersion,
+            MigrateError::EmptyInput => MigrateError::EmptyInput,
+            MigrateError::Migrate {
+                version,
+                error,
+                help,
+            } => MigrateError::Migrate {
+                version,
+                error: f(error),
+                help,
+            },
+            MigrateError::TooOld { limit } => MigrateError::TooOld { limit },
+            MigrateError::StepPanicked { version, message } => {
+                MigrateError::StepPanicked { version, message }
+            }
+        }
+    }
+}
+
+impl<E: Display> Display for MigrateError<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MigrateError::NoMatchingVersion => {
+                f.write_str("No version in the chain matches the input")
+            }
+            MigrateError::EmptyInput => f.write_str("Input is empty"),
+            MigrateError::Migrate {
+                version,
+                error,
+                help,
+            } => write_migrate(f, version, error, None, *help),
+            MigrateError::TooOld { limit } => {
+                write!(
+                    f,
+                    "Input does not match the newest {limit} versions in the chain"
+                )
+            }
+            MigrateError::StepPanicked { version, message } => {
+                write!(f, "Migrating to `{version}` panicked: {message}")
+            }
+        }
+    }
+}
+
+impl<E: std::error::Error + 'static> MigrateError<E> {
+    /// This error followed by each of its [`std::error::Error::source`]s, for printing the
+    /// full cause of a failure
+    ///
+    /// ```rust
+    /// use magic_migrate::TryMigrate;
+    #[doc = include_str!("fixtures/try_personV1_V2.txt")]
+    ///
+    /// magic_migrate::try_migrate_toml_chain!(
+    ///     error: PersonMigrationError,
+    ///     chain: [PersonV1, PersonV2],
+    /// );
+    ///
+    /// let error = PersonV2::try_from_str_migrations_result("name = 'Schneems'").unwrap_err();
+    /// let messages = error.chain().map(ToString::to_string).collect::<Vec<_>>();
+    /// assert_eq!(
+    ///     messages,
+    ///     vec!["Could not migrate from `PersonV1`: Title cannot be empty!!!"]
+    /// );
+    /// ```
+    ///
+    /// The chain's error is already part of the message, so the chain continues with the
+    /// sources of the chain's error.
+    pub fn chain(&self) -> impl Iterator<Item = &(dyn std::error::Error + 'static)> {
+        std::iter::successors(Some(self as &(dyn std::error::Error + 'static)), |error| {
+            error.source()
+        })
+    }
+
+    /// Displays the same as this error, but the alternate form (`{:#}`) also puts each
+    /// [`std::error::Error::source`] of the chain's error on its own indented line
+    ///
+    /// The chain's error only has to implement [`Display`], so the alternate form of
+    /// [`MigrateError`] itself can't reach those sources.
+    ///
+    /// ```rust
+    /// use magic_migrate::TryMigrate;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize, Debug)]
+    /// #[serde(deny_unknown_fields)]
+    /// struct ServerV1 {
+    ///     port: String,
+    /// }
+    ///
+    /// #[derive(Deserialize, Debug)]
+    /// #[serde(deny_unknown_fields)]
+    /// struct ServerV2 {
+    ///     port: u16,
+    /// }
+    ///
+    /// #[derive(Debug, thiserror::Error)]
+    /// #[error("Invalid port {0:?}")]
+    /// struct PortError(String, #[source] std::num::ParseIntError);
+    ///
+    /// impl TryFrom<ServerV1> for ServerV2 {
+    ///     type Error = PortError;
+    ///
+    ///     fn try_from(value: ServerV1) -> Result<Self, PortError> {
+    ///         match value.port.parse() {
+    ///             Ok(port) => Ok(ServerV2 { port }),
+    ///             Err(error) => Err(PortError(value.port, error)),
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// magic_migrate::try_migrate_toml_chain!(
+    ///     error: PortError,
+    ///     chain: [ServerV1, ServerV2],
+    /// );
+    ///
+    /// let error = ServerV2::try_from_str_migrations_result("port = 'http'").unwrap_err();
+    /// assert_eq!(
+    ///     format!("{:#}", error.report()),
+    ///     "Could not migrate from `ServerV1`\n\nCaused by:\n    Invalid port \"http\"\n    invalid digit found in string"
+    /// );
+    /// assert_eq!(
+    ///     format!("{}", error.report()),
+    ///     "Could not migrate from `ServerV1`: Invalid port \"http\""
+    /// );
+    /// ```
+    pub fn report(&self) -> impl Display + '_ {
+        Report(self)
+    }
+}
+
+/// Returned by [`MigrateError::report`]
+struct Report<'a, E>(&'a MigrateError<E>);
+
+impl<E: std::error::Error + 'static> Display for Report<'_, E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            MigrateError::Migrate {
+                version,
+                error,
+                help,
+            } => write_migrate(f, version, error, error.source(), *help),
+            other => Display::fmt(other, f),
+        }
+    }
+}
+
+impl<E: std::error::Error + 'static> std::error::Error for MigrateError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MigrateError::NoMatchingVersion
+            | MigrateError::EmptyInput
+            | MigrateError::TooOld { .. }
+            | MigrateError::StepPanicked { .. } => None,
+            // The chain's error is part of the message
+            MigrateError::Migrate { error, .. } => error.source(),
+        }
+    }
+}
+
+/// Writes a [`MigrateError::Migrate`]
+fn write_migrate(
+    f: &mut std::fmt::Formatter<'_>,
+    version: &str,
+    error: &dyn Display,
+    sources: Option<&(dyn std::error::Error + 'static)>,
+    help: Option<&'static str>,
+) -> std::fmt::Result {
+    write_report(
+        f,
+        format_args!("Could not migrate from `{version}`"),
+        error,
+        sources,
+    )?;
+    write_help(f, help)
+}
+
+/// Writes `message: cause`, or for the alternate form (`{:#}`) `message` followed by the
+/// cause and each of `sources` on their own indented lines
+///
+/// `sources` are the sources of `cause` when it's a [`std::error::Error`], otherwise
+/// `cause` is formatted with `{:#}` so it can render its own sources.
+pub(crate) fn write_report(
+    f: &mut std::fmt::Formatter<'_>,
+    message: std::fmt::Arguments<'_>,
+    cause: &dyn Display,
+    sources: Option<&(dyn std::error::Error + 'static)>,
+) -> std::fmt::Result {
+    if !f.alternate() {
+        return write!(f, "{message}: {cause}");
+    }
+
+    write!(f, "{message}\n\nCaused by:")?;
+    let mut indented = Indented(f);
+    if sources.is_some() {
+        write!(indented, "\n{cause}")?;
+    } else {
+        write!(indented, "\n{cause:#}")?;
+    }
+    for source in std::iter::successors(sources, |error| error.source()) {
+        write!(indented, "\n{source}")?;
+    }
+    Ok(())
+}
+
+/// Writes ` (see <help>)`, or for the alternate form (`{:#}`) `help` on its own line
+fn write_help(f: &mut std::fmt::Formatter<'_>, help: Option<&'static str>) -> std::fmt::Result {
+    match help {
+        Some(help) if f.alternate() => write!(f, "\n\nHelp: {help}"),
+        Some(help) => write!(f, " (see {help})"),
+        None => Ok(()),
+    }
+}
+
+/// Indents every line after a newline by four spaces
+struct Indented<'a, 'b>(&'a mut std::fmt::Formatter<'b>);
+
+impl std::fmt::Write for Indented<'_, '_> {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        for (index, line) in s.split('\n').enumerate() {
+            if index > 0 {
+                self.0.write_str("\n    ")?;
+            }
+            self.0.write_str(line)?;
+        }
+        Ok(())
+    }
+}
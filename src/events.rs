@@ -0,0 +1,47 @@
+//! Points in the chain walk that are reported through the `log` facade when the `log`
+//! feature is enabled, they compile to nothing otherwise
+//!
+//! Everything is logged at debug level. Failures are returned to the caller, who decides
+//! whether they are worth a warning.
+
+use crate::TryMigrate;
+
+/// Deserializing the input into the struct named `version` was tried
+pub(crate) fn attempt(version: &str, matched: bool) {
+    #[cfg(feature = "log")]
+    if matched {
+        log::debug!("Input deserialized into `{version}`");
+    } else {
+        log::debug!("Input does not deserialize into `{version}`");
+    }
+    #[cfg(not(feature = "log"))]
+    let _ = (version, matched);
+}
+
+/// Converting into `T` from the struct before it finished
+pub(crate) fn step<T: TryMigrate>(result: &Result<T, <T as TryFrom<T::TryFrom>>::Error>) {
+    #[cfg(feature = "log")]
+    match result {
+        Ok(_) => log::debug!(
+            "Migrated `{}` => `{}`",
+            <T::TryFrom as TryMigrate>::version_name(),
+            T::version_name()
+        ),
+        Err(_) => log::debug!(
+            "Could not migrate `{}` => `{}`",
+            <T::TryFrom as TryMigrate>::version_name(),
+            T::version_name()
+        ),
+    }
+    #[cfg(not(feature = "log"))]
+    let _ = result;
+}
+
+/// The preferred way of finding the struct failed and the chain of the struct named
+/// `version` is tried another way
+pub(crate) fn fallback(version: &str, reason: &str) {
+    #[cfg(feature = "log")]
+    log::debug!("Falling back for `{version}`: {reason}");
+    #[cfg(not(feature = "log"))]
+    let _ = (version, reason);
+}
@@ -0,0 +1,622 @@
+//! Helpers for running a migration chain on the contents of a file
+
+use crate::error::write_report;
+use crate::{MigrateErrorKind, TryMigrate};
+use std::any::TypeId;
+use std::error::Error;
+use std::fmt::{Debug, Display};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+type Serializer<T> = Box<dyn Fn(&T) -> Result<String, BoxError>>;
+type Walk<T> = fn(&str) -> Option<(TypeId, Result<T, <T as TryMigrate>::Error>)>;
+
+/// Errors from loading a file through a migration chain
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum LoadError<E> {
+    /// The file could not be read
+    Io(PathBuf, std::io::Error),
+    /// The file extension does not map to an enabled [`crate::Format`]
+    UnknownFormat(PathBuf),
+    /// No struct in the chain could deserialize the file contents
+    NoMatchingVersion(PathBuf),
+    /// A struct deserialized but migrating it to the latest version failed
+    Migrate(PathBuf, E),
+    /// The migrated value could not be serialized to write it back
+    Repair(PathBuf, BoxError),
+}
+
+impl<E> LoadError<E> {
+    #[must_use]
+    pub fn kind(&self) -> MigrateErrorKind {
+        match self {
+            LoadError::Io(..) => MigrateErrorKind::Io,
+            LoadError::UnknownFormat(_) => MigrateErrorKind::Format,
+            LoadError::NoMatchingVersion(_) => MigrateErrorKind::NoMatchingVersion,
+            LoadError::Migrate(..) => MigrateErrorKind::Migrate,
+            LoadError::Repair(..) => MigrateErrorKind::Serialize,
+        }
+    }
+}
+
+impl<E: Display> Display for LoadError<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoadError::Io(path, error) => write_report(
+                f,
+                format_args!("Could not read {}", path.display()),
+                error,
+                error.source(),
+            ),
+            LoadError::UnknownFormat(path) => {
+                write!(f, "Unknown format for {}", path.display())
+            }
+            LoadError::NoMatchingVersion(path) => {
+                write!(f, "No version in the chain matches {}", path.display())
+            }
+            LoadError::Migrate(path, error) => write_report(
+                f,
+                format_args!("Could not migrate {}", path.display()),
+                error,
+                None,
+            ),
+            LoadError::Repair(path, error) => write_report(
+                f,
+                format_args!("Could not serialize {}", path.display()),
+                error,
+                error.source(),
+            ),
+        }
+    }
+}
+
+impl<E: Display + Debug + 'static> std::error::Error for LoadError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LoadError::Io(_, error) => Some(error),
+            LoadError::Repair(_, error) => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// Read a file and run it through the migration chain of `T`, picking the [`crate::Format`]
+/// based on the file extension
+///
+/// Formats are enabled with their cargo features:
+///
+/// - `toml` for `.toml`
+/// - `json` for `.json`
+/// - `xml` for `.xml`
+/// - `yaml` for `.yaml` and `.yml`
+///
+/// The chain's own [`TryMigrate::deserializer`] is not used. Structs with `toml::Spanned`
+/// fields only load from `.toml` files.
+///
+/// ```rust
+/// use magic_migrate::TryMigrate;
+#[doc = include_str!("fixtures/try_personV1_V2.txt")]
+///
+/// magic_migrate::try_migrate_toml_chain!(
+///     error: PersonMigrationError,
+///     chain: [PersonV1, PersonV2],
+/// );
+///
+/// let dir = std::env::temp_dir().join("magic_migrate_load_config_doctest");
+/// std::fs::create_dir_all(&dir).unwrap();
+///
+/// let path = dir.join("person.json");
+/// std::fs::write(&path, r#"{"name": "Schneems", "title": "Dev"}"#).unwrap();
+/// let person: PersonV2 = magic_migrate::load_config(&path).unwrap();
+/// assert_eq!(person.job_title, "Dev".to_string());
+///
+/// let path = dir.join("person.yml");
+/// std::fs::write(&path, "name: Schneems\njob_title: Dev").unwrap();
+/// let person: PersonV2 = magic_migrate::load_config(&path).unwrap();
+/// assert_eq!(person.job_title, "Dev".to_string());
+///
+/// let path = dir.join("person.ini");
+/// std::fs::write(&path, "name = Schneems").unwrap();
+/// let result = magic_migrate::load_config::<PersonV2>(&path);
+/// assert!(matches!(result, Err(magic_migrate::LoadError::UnknownFormat(_))));
+/// ```
+pub fn load_config<T: TryMigrate>(
+    path: impl AsRef<Path>,
+) -> Result<T, LoadError<<T as TryMigrate>::Error>> {
+    let path = path.as_ref();
+    let contents =
+        std::fs::read_to_string(path).map_err(|error| LoadError::Io(path.to_path_buf(), error))?;
+
+    migrate_contents::<T>(path, &contents).1
+}
+
+/// Same as [`load_config`] but tries each of `paths` in order, returns the first one that
+/// loads along with its path
+///
+/// Use it while data moves from one location to another. When no path loads there is one
+/// error for each path, in order.
+///
+/// ```rust
+/// use magic_migrate::TryMigrate;
+#[doc = include_str!("fixtures/try_personV1_V2.txt")]
+///
+/// magic_migrate::try_migrate_toml_chain!(
+///     error: PersonMigrationError,
+///     chain: [PersonV1, PersonV2],
+/// );
+///
+/// let dir = std::env::temp_dir().join("magic_migrate_load_config_sources_doctest");
+/// std::fs::create_dir_all(&dir).unwrap();
+/// let new = dir.join("cache.toml");
+/// let old = dir.join("metadata.toml");
+/// let _ = std::fs::remove_file(&new);
+/// std::fs::write(&old, "name = 'Schneems'\ntitle = 'Dev'").unwrap();
+///
+/// let (path, person) = magic_migrate::load_config_sources::<PersonV2>([&new, &old]).unwrap();
+/// assert_eq!(path, old);
+/// assert_eq!(person.job_title, "Dev".to_string());
+///
+/// std::fs::remove_file(&old).unwrap();
+/// let errors = magic_migrate::load_config_sources::<PersonV2>([&new, &old]).unwrap_err();
+/// assert_eq!(errors.len(), 2);
+/// ```
+pub fn load_config_sources<T: TryMigrate>(
+    paths: impl IntoIterator<Item = impl AsRef<Path>>,
+) -> Result<(PathBuf, T), Vec<LoadError<<T as TryMigrate>::Error>>> {
+    let mut errors = Vec::new();
+    for path in paths {
+        let path = path.as_ref();
+        match load_config::<T>(path) {
+            Ok(latest) => return Ok((path.to_path_buf(), latest)),
+            Err(error) => errors.push(error),
+        }
+    }
+    Err(errors)
+}
+
+/// What [`clean_unreadable`] does with files that no struct in the chain can read
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CleanupAction {
+    /// Delete the file
+    Delete,
+    /// Move the file into this directory, which is created when missing
+    Quarantine(PathBuf),
+}
+
+/// The files [`clean_unreadable`] looked at
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Cleanup {
+    /// Files that a struct in the chain can read, including ones that fail to migrate
+    pub kept: Vec<PathBuf>,
+    /// Files that no struct could read, at their original path, which were deleted or moved
+    pub removed: Vec<PathBuf>,
+    /// Files that were left alone because their extension doesn't map to an enabled
+    /// [`crate::Format`]
+    pub skipped: Vec<PathBuf>,
+}
+
+/// Delete or quarantine every file in `dir` that no struct in the chain of `T` can read
+///
+/// Unreadable files otherwise pile up in cache directories forever. Each file's format is
+/// picked by its extension like [`load_config`], files with other extensions are skipped
+/// without being read and subdirectories aren't searched. A file that deserializes into a
+/// struct but fails to migrate is kept.
+///
+/// ```rust
+/// use magic_migrate::{CleanupAction, TryMigrate};
+#[doc = include_str!("fixtures/try_personV1_V2.txt")]
+///
+/// magic_migrate::try_migrate_toml_chain!(
+///     error: PersonMigrationError,
+///     chain: [PersonV1, PersonV2],
+/// );
+///
+/// let dir = std::env::temp_dir().join("magic_migrate_clean_unreadable_doctest");
+/// let _ = std::fs::remove_dir_all(&dir);
+/// std::fs::create_dir_all(&dir).unwrap();
+/// std::fs::write(dir.join("a.toml"), "name = 'Schneems'\ntitle = 'Dev'").unwrap();
+/// std::fs::write(dir.join("b.toml"), "unknown = true").unwrap();
+/// std::fs::write(dir.join("c.txt"), "unknown = true").unwrap();
+/// std::fs::write(dir.join("d.png"), [0x89, b'P', b'N', b'G', 0xff, 0xfe]).unwrap();
+///
+/// let quarantine = dir.join("quarantine");
+/// let cleanup =
+///     magic_migrate::clean_unreadable::<PersonV2>(&dir, &CleanupAction::Quarantine(quarantine.clone()))
+///         .unwrap();
+/// assert_eq!(cleanup.kept, vec![dir.join("a.toml")]);
+/// assert_eq!(cleanup.removed, vec![dir.join("b.toml")]);
+/// assert_eq!(cleanup.skipped, vec![dir.join("c.txt"), dir.join("d.png")]);
+/// assert!(quarantine.join("b.toml").exists());
+/// assert!(!dir.join("b.toml").exists());
+/// assert!(dir.join("d.png").exists());
+/// ```
+pub fn clean_unreadable<T: TryMigrate>(
+    dir: impl AsRef<Path>,
+    action: &CleanupAction,
+) -> Result<Cleanup, LoadError<<T as TryMigrate>::Error>> {
+    let dir = dir.as_ref();
+    fn io_error<E>(path: &Path) -> impl FnOnce(std::io::Error) -> LoadError<E> + '_ {
+        move |error| LoadError::Io(path.to_path_buf(), error)
+    }
+
+    let mut paths = std::fs::read_dir(dir)
+        .map_err(io_error(dir))?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(io_error(dir))?;
+    paths.retain(|path| path.is_file());
+    paths.sort();
+
+    let mut cleanup = Cleanup::default();
+    for path in paths {
+        let Some(walk) = walk_for::<T>(&path) else {
+            cleanup.skipped.push(path);
+            continue;
+        };
+        let readable = match std::fs::read_to_string(&path) {
+            Ok(contents) => walk(&contents).is_some(),
+            // Not UTF-8, so no format can read it
+            Err(error) if error.kind() == std::io::ErrorKind::InvalidData => false,
+            Err(error) => return Err(io_error(&path)(error)),
+        };
+
+        if readable {
+            cleanup.kept.push(path);
+            continue;
+        }
+        match action {
+            CleanupAction::Delete => std::fs::remove_file(&path).map_err(io_error(&path))?,
+            CleanupAction::Quarantine(quarantine) => {
+                std::fs::create_dir_all(quarantine).map_err(io_error(quarantine))?;
+                let file_name = path.file_name().expect("Files from read_dir have a name");
+                std::fs::rename(&path, quarantine.join(file_name)).map_err(io_error(&path))?;
+            }
+        }
+        cleanup.removed.push(path);
+    }
+    Ok(cleanup)
+}
+
+/// Run `contents` through the chain of `T` with the format from the extension of `path`,
+/// also returns the type of the struct it was deserialized into
+fn migrate_contents<T: TryMigrate>(
+    path: &Path,
+    contents: &str,
+) -> (
+    Option<TypeId>,
+    Result<T, LoadError<<T as TryMigrate>::Error>>,
+) {
+    let Some(walk) = walk_for::<T>(path) else {
+        return (None, Err(LoadError::UnknownFormat(path.to_path_buf())));
+    };
+
+    match walk(contents) {
+        Some((type_id, result)) => (
+            Some(type_id),
+            result.map_err(|error| LoadError::Migrate(path.to_path_buf(), error)),
+        ),
+        None => (None, Err(LoadError::NoMatchingVersion(path.to_path_buf()))),
+    }
+}
+
+/// The chain walk of `T` with the [`crate::Format`] picked by the extension of `path`, `None`
+/// when the extension doesn't map to an enabled format
+fn walk_for<T: TryMigrate>(path: &Path) -> Option<Walk<T>> {
+    match path.extension().and_then(std::ffi::OsStr::to_str) {
+        #[cfg(feature = "toml")]
+        Some("toml") => Some(crate::walk::walk_as::<T, crate::Toml>),
+        #[cfg(feature = "json")]
+        Some("json") => Some(crate::walk::walk_as::<T, crate::Json>),
+        #[cfg(feature = "xml")]
+        Some("xml") => Some(crate::walk::walk_as::<T, crate::Xml>),
+        #[cfg(feature = "yaml")]
+        Some("yaml" | "yml") => Some(crate::walk::walk_as::<T, crate::Yaml>),
+        _ => None,
+    }
+}
+
+/// Read, migrate, and optionally write back a file, such as a cache's metadata
+///
+/// Same as [`load_config`] but with [`CacheFile::read_repair`] a file stored as an older
+/// version is rewritten as the latest version. The latest version is written to a
+/// temporary file in the same directory and renamed over the file, so a crash never
+/// leaves it half written. With `CacheFile::lock` (`lock` feature) an exclusive advisory
+/// lock is held from the read until the write, so processes racing on the same file don't
+/// interleave.
+///
+/// ```rust
+/// use magic_migrate::{CacheFile, TryMigrate};
+#[doc = include_str!("fixtures/try_personV1_V2.txt")]
+///
+/// magic_migrate::try_migrate_toml_chain!(
+///     error: PersonMigrationError,
+///     chain: [PersonV1, PersonV2],
+/// );
+///
+/// let dir = std::env::temp_dir().join("magic_migrate_cache_file_doctest");
+/// std::fs::create_dir_all(&dir).unwrap();
+/// let path = dir.join("person.toml");
+/// std::fs::write(&path, "name = 'Schneems'\ntitle = 'Dev'").unwrap();
+///
+/// let person = CacheFile::<PersonV2>::new(&path)
+///     .read_repair(toml::to_string)
+///     .load()
+///     .unwrap();
+/// assert_eq!(person.job_title, "Dev".to_string());
+///
+/// // The latest version was written back
+/// let contents = std::fs::read_to_string(&path).unwrap();
+/// assert_eq!(contents, toml::to_string(&person).unwrap());
+/// ```
+pub struct CacheFile<T> {
+    path: PathBuf,
+    #[cfg(feature = "lock")]
+    lock: bool,
+    read_repair: Option<Serializer<T>>,
+    journal: Option<PathBuf>,
+}
+
+impl<T: TryMigrate> CacheFile<T> {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            #[cfg(feature = "lock")]
+            lock: false,
+            read_repair: None,
+            journal: None,
+        }
+    }
+
+    /// Hold an exclusive advisory lock while the file is read, migrated, and written
+    ///
+    /// The lock is taken on `<file name>.lock` next to the file, which is created when
+    /// missing and left in place. The file itself is replaced when it's written back, so a
+    /// lock on it would not be held by the next process to open it. Advisory locks only
+    /// coordinate processes that also lock the file.
+    ///
+    /// Enabled with the `lock` feature, which needs Rust 1.89 or later.
+    ///
+    /// ```rust
+    /// use magic_migrate::{CacheFile, TryMigrate};
+    #[doc = include_str!("fixtures/try_personV1_V2.txt")]
+    ///
+    /// magic_migrate::try_migrate_toml_chain!(
+    ///     error: PersonMigrationError,
+    ///     chain: [PersonV1, PersonV2],
+    /// );
+    ///
+    /// let dir = std::env::temp_dir().join("magic_migrate_cache_file_lock_doctest");
+    /// std::fs::create_dir_all(&dir).unwrap();
+    /// let path = dir.join("person.toml");
+    /// std::fs::write(&path, "name = 'Schneems'\ntitle = 'Dev'").unwrap();
+    ///
+    /// let load = || {
+    ///     CacheFile::<PersonV2>::new(&path)
+    ///         .lock()
+    ///         .read_repair(toml::to_string)
+    ///         .load()
+    /// };
+    /// let titles = std::thread::scope(|scope| {
+    ///     (0..4)
+    ///         .map(|_| scope.spawn(|| load().unwrap().job_title))
+    ///         .collect::<Vec<_>>()
+    ///         .into_iter()
+    ///         .map(|handle| handle.join().unwrap())
+    ///         .collect::<Vec<_>>()
+    /// });
+    /// assert_eq!(titles, vec!["Dev".to_string(); 4]);
+    /// assert!(dir.join("person.toml.lock").exists());
+    /// ```
+    #[cfg(feature = "lock")]
+    #[must_use]
+    pub fn lock(mut self) -> Self {
+        self.lock = true;
+        self
+    }
+
+    /// Write files that were stored as an older version back using `serialize`
+    #[must_use]
+    pub fn read_repair<F, E>(mut self, serialize: F) -> Self
+    where
+        F: Fn(&T) -> Result<String, E> + 'static,
+        E: Into<BoxError>,
+    {
+        self.read_repair = Some(Box::new(move |value| serialize(value).map_err(Into::into)));
+        self
+    }
+
+    /// Append a line to the file at `path` each time an older version is migrated or
+    /// loading fails
+    ///
+    /// Each line has the seconds since the Unix epoch, the [`TryMigrate::version_name`] the
+    /// file was stored as (`-` when unknown), the version it was migrated to, and the
+    /// outcome, separated by tabs. Every failure is recorded, including a file that can't be
+    /// read or written back. Loading a file that's already the latest version isn't
+    /// recorded. Writing the journal is best-effort, a journal that can't be written never
+    /// fails a load.
+    ///
+    /// ```rust
+    /// use magic_migrate::{CacheFile, TryMigrate};
+    #[doc = include_str!("fixtures/try_personV1_V2.txt")]
+    ///
+    /// magic_migrate::try_migrate_toml_chain!(
+    ///     error: PersonMigrationError,
+    ///     chain: [PersonV1, PersonV2],
+    /// );
+    ///
+    /// let dir = std::env::temp_dir().join("magic_migrate_cache_file_journal_doctest");
+    /// std::fs::create_dir_all(&dir).unwrap();
+    /// let path = dir.join("person.toml");
+    /// let journal = dir.join("person.journal");
+    /// let _ = std::fs::remove_file(&journal);
+    ///
+    /// let cache = CacheFile::<PersonV2>::new(&path)
+    ///     .read_repair(toml::to_string)
+    ///     .journal(&journal);
+    ///
+    /// std::fs::write(&path, "name = 'Schneems'\ntitle = 'Dev'").unwrap();
+    /// cache.load().unwrap();
+    /// cache.load().unwrap();
+    /// std::fs::write(&path, "name = 'Schneems'").unwrap();
+    /// assert!(cache.load().is_err());
+    /// std::fs::remove_file(&path).unwrap();
+    /// assert!(cache.load().is_err());
+    ///
+    /// let entries = std::fs::read_to_string(&journal).unwrap();
+    /// let entries = entries
+    ///     .lines()
+    ///     .map(|line| line.split('\t').skip(1).collect::<Vec<_>>())
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(entries.len(), 3);
+    /// assert_eq!(entries[0], vec!["PersonV1", "PersonV2", "migrated and written back"]);
+    /// assert_eq!(entries[1][..2], ["PersonV1", "PersonV2"]);
+    /// assert!(entries[1][2].starts_with("failed: Could not migrate"));
+    /// assert_eq!(entries[2][..2], ["-", "PersonV2"]);
+    /// assert!(entries[2][2].starts_with("failed: "));
+    ///
+    /// // A journal that can't be written doesn't fail the load
+    /// std::fs::write(&path, "name = 'Schneems'\ntitle = 'Dev'").unwrap();
+    /// let person = CacheFile::<PersonV2>::new(&path).journal(&dir).load().unwrap();
+    /// assert_eq!(person.job_title, "Dev");
+    /// ```
+    #[must_use]
+    pub fn journal(mut self, path: impl Into<PathBuf>) -> Self {
+        self.journal = Some(path.into());
+        self
+    }
+
+    /// Read the file and migrate it to the latest version
+    pub fn load(&self) -> Result<T, LoadError<<T as TryMigrate>::Error>> {
+        let mut from = None;
+        let result = self.migrate(&mut from);
+        match &result {
+            Ok((_, Some(outcome))) => self.record(from, outcome),
+            Ok((_, None)) => {}
+            Err(error) => self.record(from, &format!("failed: {error}")),
+        }
+        result.map(|(latest, _)| latest)
+    }
+
+    /// Read, migrate, and write back the file, returning the outcome to record when the file
+    /// wasn't already the latest version
+    ///
+    /// `from` is set to the version the file was stored as, once it's known.
+    fn migrate(
+        &self,
+        from: &mut Option<TypeId>,
+    ) -> Result<(T, Option<&'static str>), LoadError<<T as TryMigrate>::Error>> {
+        let path = self.path.as_path();
+        let io_error = |error| LoadError::Io(path.to_path_buf(), error);
+
+        #[cfg(feature = "lock")]
+        let _lock = self.lock_file()?;
+
+        let contents = std::fs::read_to_string(path).map_err(io_error)?;
+        let (type_id, result) = migrate_contents::<T>(path, &contents);
+        *from = type_id;
+        let latest = result?;
+        if type_id == Some(TypeId::of::<T>()) {
+            return Ok((latest, None));
+        }
+
+        let mut outcome = "migrated";
+        if let Some(serialize) = &self.read_repair {
+            let repaired =
+                serialize(&latest).map_err(|error| LoadError::Repair(path.to_path_buf(), error))?;
+            replace(path, &repaired).map_err(io_error)?;
+            outcome = "migrated and written back";
+        }
+
+        // The lock is released when `_lock` is dropped
+        Ok((latest, Some(outcome)))
+    }
+
+    /// Open and lock `<file name>.lock` next to the file when locking, the lock is released
+    /// when the returned file is dropped
+    #[cfg(feature = "lock")]
+    fn lock_file(&self) -> Result<Option<std::fs::File>, LoadError<<T as TryMigrate>::Error>> {
+        if !self.lock {
+            return Ok(None);
+        }
+        let mut name = self.path.file_name().unwrap_or_default().to_os_string();
+        name.push(".lock");
+        let path = self.path.with_file_name(name);
+        std::fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)
+            .and_then(|file| file.lock().map(|()| Some(file)))
+            .map_err(|error| LoadError::Io(path, error))
+    }
+
+    /// Append an entry to the journal, if there is one
+    ///
+    /// Errors are ignored, the outcome of the load is more useful than a failure to record it.
+    fn record(&self, from: Option<TypeId>, outcome: &str) {
+        let Some(journal) = &self.journal else {
+            return;
+        };
+        let from = crate::walk::links::<T>()
+            .into_iter()
+            .find(|link| Some(link.type_id) == from)
+            .map_or("-", |link| link.name);
+        let seconds = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        let entry = format!(
+            "{seconds}\t{from}\t{}\t{}\n",
+            T::version_name(),
+            outcome.replace(['\t', '\n'], " ")
+        );
+
+        let _ = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(journal)
+            .and_then(|mut file| file.write_all(entry.as_bytes()));
+    }
+}
+
+/// Write `contents` to a temporary file next to `path` and rename it over `path`
+fn replace(path: &Path, contents: &str) -> std::io::Result<()> {
+    /// Keeps the temporary files of loads running on different threads apart
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let mut name = std::ffi::OsString::from(".");
+    name.push(path.file_name().unwrap_or_default());
+    name.push(format!(
+        ".{}.{}.tmp",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    let temporary = path.with_file_name(name);
+
+    let result = std::fs::File::create(&temporary)
+        .and_then(|mut file| {
+            file.write_all(contents.as_bytes())?;
+            file.sync_all()
+        })
+        .and_then(|()| std::fs::rename(&temporary, path));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temporary);
+    }
+    result
+}
+
+impl<T> Debug for CacheFile<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut debug = f.debug_struct("CacheFile");
+        debug.field("path", &self.path);
+        #[cfg(feature = "lock")]
+        debug.field("lock", &self.lock);
+        debug
+            .field("read_repair", &self.read_repair.is_some())
+            .field("journal", &self.journal)
+            .finish()
+    }
+}
@@ -0,0 +1,249 @@
+//! Serialization formats that can be chosen at runtime
+//!
+//! A chain's [`crate::TryMigrate::deserializer`] is fixed when the chain is defined. When
+//! the same structs need to be loaded from more than one format, pass a [`Format`] to
+//! [`crate::TryMigrate::try_from_str_migrations_as`] instead.
+
+/// A serialization format that can be used to run a migration chain
+///
+/// This mirrors [`crate::TryMigrate::deserializer`] so any deserializer that works in a
+/// chain also works here.
+pub trait Format {
+    fn deserializer<'de>(input: &str) -> impl serde::de::Deserializer<'de>;
+}
+
+/// TOML via the `toml` crate
+#[cfg(feature = "toml")]
+#[derive(Debug, Clone, Copy)]
+pub struct Toml;
+
+#[cfg(feature = "toml")]
+impl Format for Toml {
+    fn deserializer<'de>(input: &str) -> impl serde::de::Deserializer<'de> {
+        toml::Deserializer::new(input)
+    }
+}
+
+/// JSON via the `serde_json` crate
+///
+/// Input with anything but whitespace after the JSON value does not deserialize, so a
+/// struct can't match a prefix of the input.
+///
+/// ```rust
+/// use magic_migrate::Format;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Person {
+///     name: String,
+/// }
+///
+/// let deserializer = magic_migrate::Json::deserializer(r#"{"name": "Schneems"} "#);
+/// assert!(Person::deserialize(deserializer).is_ok());
+///
+/// let deserializer = magic_migrate::Json::deserializer(r#"{"name": "Schneems"} {"title": "Dev"}"#);
+/// assert!(Person::deserialize(deserializer).is_err());
+/// ```
+#[cfg(feature = "json")]
+#[derive(Debug, Clone, Copy)]
+pub struct Json;
+
+#[cfg(feature = "json")]
+impl Format for Json {
+    fn deserializer<'de>(input: &str) -> impl serde::de::Deserializer<'de> {
+        json::JsonDeserializer(serde_json::Deserializer::from_reader(input.as_bytes()))
+    }
+}
+
+/// YAML via the `serde_yaml` crate
+#[cfg(feature = "yaml")]
+#[derive(Debug, Clone, Copy)]
+pub struct Yaml;
+
+#[cfg(feature = "yaml")]
+impl Format for Yaml {
+    fn deserializer<'de>(input: &str) -> impl serde::de::Deserializer<'de> {
+        // `from_str` ties the deserializer to the lifetime of `input`, an owned reader does not
+        serde_yaml::Deserializer::from_reader(std::io::Cursor::new(input.to_owned()))
+    }
+}
+
+/// XML via the `quick-xml` crate
+///
+/// The name of the root element is ignored, child elements and attributes map to fields.
+/// Input with anything but whitespace, comments, or processing instructions after the root
+/// element does not deserialize, so a struct can't match a prefix of the input.
+///
+/// ```rust
+/// use magic_migrate::{Format, TryMigrate};
+#[doc = include_str!("fixtures/try_personV1_V2.txt")]
+///
+/// magic_migrate::try_migrate_deserializer_chain!(
+///     error: PersonMigrationError,
+///     deserializer: magic_migrate::Xml::deserializer,
+///     chain: [PersonV1, PersonV2],
+/// );
+///
+/// let person = PersonV2::try_from_str_migrations(
+///     "<person><name>Schneems</name><title>Dev</title></person>"
+/// )
+/// .unwrap()
+/// .unwrap();
+/// assert_eq!(person.job_title, "Dev".to_string());
+///
+/// let result = PersonV2::try_from_str_migrations(
+///     "<person><name>Schneems</name><title>Dev</title></person><title>Ops</title>"
+/// );
+/// assert!(result.is_none());
+/// ```
+#[cfg(feature = "xml")]
+#[derive(Debug, Clone, Copy)]
+pub struct Xml;
+
+#[cfg(feature = "xml")]
+impl Format for Xml {
+    fn deserializer<'de>(input: &str) -> impl serde::de::Deserializer<'de> {
+        xml::XmlDeserializer(
+            quick_xml::de::Deserializer::from_reader(input.as_bytes()),
+            input,
+        )
+    }
+}
+
+/// Implements every [`serde::Deserializer`] method by forwarding to `&mut self.0`, then
+/// calling the wrapper's `finish` so a value only counts when all of the input was used
+#[cfg(any(feature = "json", feature = "xml"))]
+macro_rules! forward_to_inner {
+    () => {
+        forward_to_inner! {
+            deserialize_any();
+            deserialize_bool();
+            deserialize_i8();
+            deserialize_i16();
+            deserialize_i32();
+            deserialize_i64();
+            deserialize_i128();
+            deserialize_u8();
+            deserialize_u16();
+            deserialize_u32();
+            deserialize_u64();
+            deserialize_u128();
+            deserialize_f32();
+            deserialize_f64();
+            deserialize_char();
+            deserialize_str();
+            deserialize_string();
+            deserialize_bytes();
+            deserialize_byte_buf();
+            deserialize_option();
+            deserialize_unit();
+            deserialize_unit_struct(name: &'static str);
+            deserialize_newtype_struct(name: &'static str);
+            deserialize_seq();
+            deserialize_tuple(len: usize);
+            deserialize_tuple_struct(name: &'static str, len: usize);
+            deserialize_map();
+            deserialize_struct(name: &'static str, fields: &'static [&'static str]);
+            deserialize_enum(name: &'static str, variants: &'static [&'static str]);
+            deserialize_identifier();
+            deserialize_ignored_any();
+        }
+    };
+    ($($method:ident($($arg:ident: $ty:ty),*);)*) => {
+        $(
+            fn $method<V: serde::de::Visitor<'de>>(
+                mut self,
+                $($arg: $ty,)*
+                visitor: V,
+            ) -> Result<V::Value, Self::Error> {
+                let value = (&mut self.0).$method($($arg,)* visitor)?;
+                self.finish()?;
+                Ok(value)
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "json")]
+mod json {
+    use serde_json::de::IoRead;
+
+    /// `serde_json` only implements [`serde::Deserializer`] for `&mut Deserializer`,
+    /// this owns the deserializer so it can be returned from [`super::Format::deserializer`]
+    ///
+    /// Uses an [`IoRead`] since a `StrRead` ties the deserializer to the lifetime of the input
+    pub(crate) struct JsonDeserializer<'a>(pub(crate) serde_json::Deserializer<IoRead<&'a [u8]>>);
+
+    impl JsonDeserializer<'_> {
+        /// Errors on trailing characters, otherwise a prefix of the input could match a
+        /// struct in the chain
+        fn finish(&mut self) -> Result<(), serde_json::Error> {
+            self.0.end()
+        }
+    }
+
+    impl<'de> serde::Deserializer<'de> for JsonDeserializer<'_> {
+        type Error = serde_json::Error;
+
+        forward_to_inner!();
+    }
+}
+
+#[cfg(feature = "xml")]
+mod xml {
+    use quick_xml::de::IoReader;
+    use quick_xml::events::Event;
+    use quick_xml::DeError;
+
+    /// `quick-xml` only implements [`serde::Deserializer`] for `&mut Deserializer`,
+    /// this owns the deserializer so it can be returned from [`super::Format::deserializer`]
+    ///
+    /// Also holds the input, the deserializer doesn't expose what's left after the root element
+    pub(crate) struct XmlDeserializer<'de, 'a>(
+        pub(crate) quick_xml::de::Deserializer<'de, IoReader<&'a [u8]>>,
+        pub(crate) &'a str,
+    );
+
+    impl XmlDeserializer<'_, '_> {
+        /// Errors on content after the root element, otherwise a prefix of the input could
+        /// match a struct in the chain
+        fn finish(&mut self) -> Result<(), DeError> {
+            let mut reader = quick_xml::Reader::from_str(self.1);
+            let mut depth = 0_usize;
+            let mut closed = false;
+            loop {
+                let event = reader.read_event()?;
+                if closed {
+                    match event {
+                        Event::Eof => return Ok(()),
+                        Event::Comment(_) | Event::PI(_) => {}
+                        Event::Text(text) if text.trim().is_empty() => {}
+                        _ => {
+                            return Err(DeError::Custom(format!(
+                                "trailing content after the root element at byte {}",
+                                reader.buffer_position()
+                            )))
+                        }
+                    }
+                } else {
+                    match event {
+                        Event::Eof => return Ok(()),
+                        Event::Start(_) => depth += 1,
+                        Event::End(_) => {
+                            depth = depth.saturating_sub(1);
+                            closed = depth == 0;
+                        }
+                        Event::Empty(_) => closed = depth == 0,
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+
+    impl<'de> serde::Deserializer<'de> for XmlDeserializer<'de, '_> {
+        type Error = quick_xml::DeError;
+
+        forward_to_inner!();
+    }
+}
@@ -0,0 +1,97 @@
+//! A startup check that every struct in a chain still migrates
+
+use crate::{walk, Samples, TryMigrate};
+use std::fmt::Display;
+
+/// Whether the sample of every struct in a chain migrates to the latest version, returned
+/// by [`ChainHealth::check`]
+///
+/// Run it when a service or buildpack starts so a broken link fails right away instead of
+/// on the first user with old data.
+///
+/// ```rust
+/// use magic_migrate::{ChainHealth, Samples, TryMigrate};
+#[doc = include_str!("fixtures/try_personV1_V2.txt")]
+///
+/// magic_migrate::try_migrate_toml_chain!(
+///     error: PersonMigrationError,
+///     chain: [PersonV1, PersonV2],
+/// );
+///
+/// let samples = Samples::<PersonV2>::new()
+///     .sample::<PersonV1, _>(|| toml::to_string(&PersonV1 { name: "Schneems".to_string(), title: None }))
+///     .sample::<PersonV2, _>(|| {
+///         toml::to_string(&PersonV2 { name: "Schneems".to_string(), job_title: "Dev".to_string() })
+///     });
+///
+/// let health = ChainHealth::check(&samples);
+/// assert!(!health.is_healthy());
+/// assert_eq!(
+///     health.to_string(),
+///     "`PersonV1`: Could not migrate from `PersonV1`: Title cannot be empty!!!\n`PersonV2`: ok"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ChainHealth {
+    /// The [`TryMigrate::version_name`] of every struct in the chain, oldest first, and what
+    /// went wrong with its sample
+    pub versions: Vec<(&'static str, Option<String>)>,
+}
+
+impl ChainHealth {
+    /// Migrate the sample of every struct in the chain of `T` to `T`
+    ///
+    /// A struct is unhealthy when its sample is missing or can't be built, deserializes into
+    /// a different struct, or fails to migrate. Panics in a conversion step are caught and
+    /// reported.
+    pub fn check<T>(samples: &Samples<T>) -> Self
+    where
+        T: TryMigrate,
+        <T as TryMigrate>::Error: Display,
+    {
+        let versions = walk::links::<T>()
+            .into_iter()
+            .map(|link| {
+                let problem = match samples.payload(&link) {
+                    Err(error) => Some(error.to_string()),
+                    Ok(payload) => match walk::walk_catching::<T>(&payload) {
+                        None => Some("No version in the chain matches the sample".to_string()),
+                        Some((matched, _)) if matched.type_id != link.type_id => {
+                            Some(format!("Sample is detected as `{}`", matched.name))
+                        }
+                        Some((_, Err(error))) => Some(error.to_string()),
+                        Some((_, Ok(_))) => None,
+                    },
+                };
+                (link.name, problem)
+            })
+            .collect();
+        Self { versions }
+    }
+
+    /// True when every struct's sample migrated
+    #[must_use]
+    pub fn is_healthy(&self) -> bool {
+        self.versions.iter().all(|(_, problem)| problem.is_none())
+    }
+
+    /// The versions with a problem and what it was
+    pub fn problems(&self) -> impl Iterator<Item = (&'static str, &str)> {
+        self.versions
+            .iter()
+            .filter_map(|(name, problem)| problem.as_deref().map(|problem| (*name, problem)))
+    }
+}
+
+impl Display for ChainHealth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (index, (name, problem)) in self.versions.iter().enumerate() {
+            if index > 0 {
+                f.write_str("\n")?;
+            }
+            write!(f, "`{name}`: {}", problem.as_deref().unwrap_or("ok"))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,89 @@
+use crate::schema::{fnv1a, FNV_OFFSET};
+use std::fmt::Display;
+
+/// The line that [`Integrity::seal`] appends, followed by the tag
+const TAG_PREFIX: &str = "\n# magic_migrate-integrity: ";
+
+/// Tags data when it's written and verifies the tag when it's read, before anything is
+/// deserialized
+///
+/// Used by [`crate::TryMigrate::try_from_str_migrations_verified`]. [`Checksum`] catches
+/// accidental corruption, to catch tampering implement [`Integrity::tag`] with a keyed hash
+/// such as an HMAC.
+///
+/// ```rust
+/// use magic_migrate::{Checksum, Integrity, IntegrityError};
+///
+/// let sealed = Checksum.seal("name = 'Schneems'");
+/// assert_eq!(Checksum.open(&sealed), Ok("name = 'Schneems'"));
+///
+/// let tampered = sealed.replace("Schneems", "Richard");
+/// assert_eq!(Checksum.open(&tampered), Err(IntegrityError::Mismatch));
+/// assert_eq!(Checksum.open("name = 'Schneems'"), Err(IntegrityError::Missing));
+/// ```
+pub trait Integrity {
+    /// The tag for `data`, it must not contain a newline
+    fn tag(&self, data: &str) -> String;
+
+    /// True when `tag` is the tag for `data`, compares in constant time by default
+    fn verify(&self, data: &str, tag: &str) -> bool {
+        let expected = self.tag(data);
+        expected.len() == tag.len()
+            && expected
+                .bytes()
+                .zip(tag.bytes())
+                .fold(0, |diff, (a, b)| diff | (a ^ b))
+                == 0
+    }
+
+    /// `data` followed by a line with its tag
+    fn seal(&self, data: &str) -> String {
+        format!("{data}{TAG_PREFIX}{}\n", self.tag(data))
+    }
+
+    /// The data from [`Integrity::seal`] with the tag removed, errors when the tag is
+    /// missing or does not match
+    fn open<'a>(&self, input: &'a str) -> Result<&'a str, IntegrityError> {
+        let (data, tag) = input
+            .rsplit_once(TAG_PREFIX)
+            .ok_or(IntegrityError::Missing)?;
+        let tag = tag.strip_suffix('\n').unwrap_or(tag);
+        if self.verify(data, tag) {
+            Ok(data)
+        } else {
+            Err(IntegrityError::Mismatch)
+        }
+    }
+}
+
+/// A 64 bit FNV-1a checksum, it detects corruption but is not a defense against tampering
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Checksum;
+
+impl Integrity for Checksum {
+    fn tag(&self, data: &str) -> String {
+        let hash = fnv1a(FNV_OFFSET, data.as_bytes());
+        format!("fnv1a64:{hash:016x}")
+    }
+}
+
+/// Input was rejected by an [`Integrity`] check before it was deserialized
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum IntegrityError {
+    /// The input has no integrity tag
+    Missing,
+    /// The tag does not match the data, it was modified after it was sealed
+    Mismatch,
+}
+
+impl Display for IntegrityError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IntegrityError::Missing => f.write_str("Input has no integrity tag"),
+            IntegrityError::Mismatch => f.write_str("Input does not match its integrity tag"),
+        }
+    }
+}
+
+impl std::error::Error for IntegrityError {}
@@ -0,0 +1,124 @@
+use crate::walk;
+use crate::TryMigrate;
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::fmt::Debug;
+
+type Jump<T> = Box<dyn Fn(Box<dyn Any>) -> Result<T, <T as TryMigrate>::Error>>;
+
+/// Shortcut conversions straight to the latest version `T` from older structs in its chain
+///
+/// When the input matches a struct with a registered jump, its `TryFrom` impl for `T` is
+/// used instead of converting through every struct in between. Other inputs migrate one
+/// link at a time as usual. Declare jumps with [`crate::try_migrate_jumps!`] or
+/// [`Jumps::jump`].
+///
+/// ```rust
+/// use magic_migrate::TryMigrate;
+#[doc = include_str!("fixtures/try_personV1_V2.txt")]
+///
+/// #[derive(Deserialize, Serialize, Debug)]
+/// struct PersonV3 {
+///     name: String,
+///     job_title: String,
+///     nickname: Option<String>,
+/// }
+///
+/// impl From<PersonV2> for PersonV3 {
+///     fn from(value: PersonV2) -> Self {
+///         PersonV3 { name: value.name, job_title: value.job_title, nickname: None }
+///     }
+/// }
+///
+/// // Skips PersonV2, even when the title is missing
+/// impl From<PersonV1> for PersonV3 {
+///     fn from(value: PersonV1) -> Self {
+///         PersonV3 {
+///             name: value.name,
+///             job_title: value.title.unwrap_or_else(|| "Unknown".to_string()),
+///             nickname: None,
+///         }
+///     }
+/// }
+///
+/// magic_migrate::try_migrate_toml_chain!(
+///     error: PersonMigrationError,
+///     chain: [PersonV1, PersonV2, PersonV3],
+/// );
+///
+/// let jumps = magic_migrate::try_migrate_jumps!([PersonV1] => PersonV3);
+///
+/// let person = jumps.try_from_str_migrations("name = 'Schneems'").unwrap().unwrap();
+/// assert_eq!(person.job_title, "Unknown".to_string());
+///
+/// // Without the jump PersonV1 => PersonV2 fails
+/// assert!(PersonV3::try_from_str_migrations("name = 'Schneems'").unwrap().is_err());
+/// ```
+pub struct Jumps<T: TryMigrate> {
+    jumps: HashMap<TypeId, Jump<T>>,
+}
+
+impl<T: TryMigrate> Jumps<T> {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            jumps: HashMap::new(),
+        }
+    }
+
+    /// Convert input that matches `V` straight to `T` with `T`'s `TryFrom<V>` impl
+    #[must_use]
+    pub fn jump<V>(mut self) -> Self
+    where
+        V: TryMigrate,
+        T: TryFrom<V>,
+        <T as TryFrom<V>>::Error: Into<<T as TryMigrate>::Error>,
+    {
+        self.jumps.insert(
+            TypeId::of::<V>(),
+            Box::new(|value| {
+                let value = value
+                    .downcast::<V>()
+                    .expect("Jumps are registered by the type they convert from");
+                T::try_from(*value).map_err(Into::into)
+            }),
+        );
+        self
+    }
+
+    /// Same as [`TryMigrate::try_from_str_migrations`] but uses a registered jump when the
+    /// matching struct has one
+    pub fn try_from_str_migrations(
+        &self,
+        input: &str,
+    ) -> Option<Result<T, <T as TryMigrate>::Error>> {
+        walk::links::<T>().into_iter().rev().find_map(|link| {
+            let value = (link.deserialize)(input)?;
+            match self.jumps.get(&link.type_id) {
+                Some(jump) => Some(jump(value)),
+                None => walk::walk_to::<T>(input, &|matched| matched.type_id == link.type_id)
+                    .map(|(_, result)| result),
+            }
+        })
+    }
+}
+
+impl<T: TryMigrate> Default for Jumps<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: TryMigrate> Debug for Jumps<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let from = walk::links::<T>()
+            .into_iter()
+            .filter(|link| self.jumps.contains_key(&link.type_id))
+            .map(|link| link.name)
+            .collect::<Vec<_>>();
+        f.debug_struct("Jumps")
+            .field("to", &T::version_name())
+            .field("from", &from)
+            .finish()
+    }
+}
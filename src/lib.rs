@@ -1,8 +1,103 @@
 #![doc = include_str!("../README.md")]
 
+mod adaptive;
+mod census;
+mod check;
+mod compressed;
+#[cfg(feature = "config")]
+mod config_rs;
+mod docs;
+mod dynamic;
+mod envelope;
+mod error;
+mod events;
+#[cfg(any(feature = "toml", feature = "json", feature = "xml", feature = "yaml"))]
+mod file;
+mod format;
+mod health;
+mod integrity;
+mod jumps;
+mod limits;
+mod memoized;
+mod metadata;
+mod omit;
+mod plan;
+mod policy;
+mod provenance;
+mod rename;
+mod salvage;
+mod samples;
+mod schema;
+#[cfg(feature = "toml")]
+mod sections;
+mod snippet;
+mod store;
+mod strategy;
+mod testing;
+mod walk;
+mod warnings;
+#[cfg(all(
+    feature = "watch",
+    any(feature = "toml", feature = "json", feature = "xml", feature = "yaml")
+))]
+mod watch;
+
+pub use adaptive::Adaptive;
+pub use census::Census;
+pub use check::DeserializerMismatch;
+pub use compressed::{Codec, Compressed};
+#[cfg(feature = "config")]
+pub use config_rs::{try_from_config_migrations, MigrateSource};
+pub use dynamic::DynamicChain;
+pub use envelope::{BinaryFormat, Envelope, EnvelopeError};
+pub use error::{MigrateError, MigrateErrorKind};
+#[cfg(any(feature = "toml", feature = "json", feature = "xml", feature = "yaml"))]
+pub use file::{
+    clean_unreadable, load_config, load_config_sources, CacheFile, Cleanup, CleanupAction,
+    LoadError,
+};
+pub use format::Format;
+#[cfg(feature = "json")]
+pub use format::Json;
+#[cfg(feature = "toml")]
+pub use format::Toml;
+#[cfg(feature = "xml")]
+pub use format::Xml;
+#[cfg(feature = "yaml")]
+pub use format::Yaml;
+pub use health::ChainHealth;
+pub use integrity::{Checksum, Integrity, IntegrityError};
+pub use jumps::Jumps;
+pub use limits::{LimitExceeded, Limits};
+pub use memoized::Memoized;
+pub use metadata::{ChainMetadata, VersionMetadata};
+pub use omit::Omitting;
+pub use plan::Plan;
+pub use policy::{EmptyInputPolicy, RecoveryPolicy};
+pub use provenance::Provenance;
+pub use rename::RenameKeys;
+pub use salvage::Salvaged;
+pub use samples::{SampleError, Samples};
+pub use schema::Schema;
+#[cfg(feature = "toml")]
+pub use sections::{SectionError, TomlSections};
 use serde::de::DeserializeOwned;
+pub use snippet::WithSnippet;
 use std::any::{Any, TypeId};
+use std::borrow::Cow;
 use std::fmt::{Debug, Display};
+pub use store::{MigratingStore, StorageBackend, StoreError};
+pub use strategy::{Candidate, NewestFirst, OldestFirst, ResolutionStrategy};
+pub use testing::{
+    assert_fixtures_migrate, assert_optional_fields_unambiguous, assert_schema_snapshots,
+    assert_versions_distinguishable, UPDATE_SNAPSHOTS_ENV,
+};
+pub use warnings::MigrationWarning;
+#[cfg(all(
+    feature = "watch",
+    any(feature = "toml", feature = "json", feature = "xml", feature = "yaml")
+))]
+pub use watch::ConfigWatch;
 
 /// Use the [`Migrate`] trait when structs can be infallibly migrated
 /// from one version to the next. Use the [`TryMigrate`] trait when
@@ -95,7 +190,7 @@ pub trait Migrate: From<Self::From> + Any + DeserializeOwned + Debug {
         if let Ok(instance) = Self::deserialize(Self::deserializer(input)) {
             Some(instance)
         } else if TypeId::of::<Self>() == TypeId::of::<Self::From>() {
-            return None;
+            None
         } else {
             <Self::From as Migrate>::from_str_migrations(input).map(Into::into)
         }
@@ -193,27 +288,1529 @@ pub trait TryMigrate: TryFrom<Self::TryFrom> + Any + DeserializeOwned + Debug {
     /// into structs
     fn deserializer<'de>(input: &str) -> impl serde::de::Deserializer<'de>;
 
+    /// Set to `true` on a struct that intentionally uses a different deserializer than the
+    /// struct it migrates from, so [`TryMigrate::check_deserializers`] allows it
+    const CHANGES_FORMAT: bool = false;
+
     type Error: From<<Self as TryFrom<<Self as TryMigrate>::TryFrom>>::Error>
         + From<<<Self as TryMigrate>::TryFrom as TryMigrate>::Error>
         + Display
         + Debug;
 
+    /// Transform raw input before any struct in the chain is deserialized, for example
+    /// to decompress, decrypt, or strip framing. Used by [`TryMigrate::try_from_slice_migrations`].
+    ///
+    /// Implement it on the first struct in the chain, later links delegate to the
+    /// struct they migrate from. The default returns the input unchanged.
+    ///
+    /// ```rust
+    /// use magic_migrate::TryMigrate;
+    /// use serde::{Deserialize, Serialize};
+    /// use std::borrow::Cow;
+    ///
+    /// #[derive(Deserialize, Serialize, Debug)]
+    /// struct PersonV1 {
+    ///     name: String,
+    /// }
+    ///
+    /// #[derive(Deserialize, Serialize, Debug)]
+    /// struct PersonV2 {
+    ///     full_name: String,
+    /// }
+    ///
+    /// impl From<PersonV1> for PersonV2 {
+    ///     fn from(value: PersonV1) -> Self {
+    ///         PersonV2 { full_name: value.name }
+    ///     }
+    /// }
+    ///
+    /// #[derive(Debug, thiserror::Error)]
+    /// enum PersonError {
+    ///     #[error("Missing header")]
+    ///     MissingHeader,
+    /// }
+    ///
+    /// magic_migrate::try_migrate_error_from!(PersonError { Infallible });
+    ///
+    /// impl TryMigrate for PersonV1 {
+    ///     type TryFrom = Self;
+    ///     type Error = PersonError;
+    ///
+    ///     fn deserializer<'de>(input: &str) -> impl serde::de::Deserializer<'de> {
+    ///         toml::Deserializer::new(input)
+    ///     }
+    ///
+    ///     fn preprocess(input: &[u8]) -> Result<Cow<'_, [u8]>, PersonError> {
+    ///         input
+    ///             .strip_prefix(b"PERSON\n")
+    ///             .map(Cow::Borrowed)
+    ///             .ok_or(PersonError::MissingHeader)
+    ///     }
+    /// }
+    ///
+    /// // Links inherit `preprocess` from `PersonV1`
+    /// magic_migrate::try_migrate_link!(PersonV1, PersonV2);
+    ///
+    /// let person = PersonV2::try_from_slice_migrations(b"PERSON\nname = 'Schneems'")
+    ///     .unwrap()
+    ///     .unwrap();
+    /// assert_eq!(person.full_name, "Schneems".to_string());
+    ///
+    /// let result = PersonV2::try_from_slice_migrations(b"name = 'Schneems'");
+    /// assert!(matches!(result, Some(Err(PersonError::MissingHeader))));
+    /// ```
+    fn preprocess(input: &[u8]) -> Result<Cow<'_, [u8]>, <Self as TryMigrate>::Error> {
+        if TypeId::of::<Self>() == TypeId::of::<Self::TryFrom>() {
+            Ok(Cow::Borrowed(input))
+        } else {
+            <Self::TryFrom as TryMigrate>::preprocess(input).map_err(Into::into)
+        }
+    }
+
+    /// Remove secrets from input before it's included in the snippet of
+    /// [`TryMigrate::try_from_str_migrations_with_snippet`]
+    ///
+    /// Implement it on the first struct in the chain, later links delegate to the struct
+    /// they migrate from. The default returns the input unchanged. Only snippets are
+    /// redacted, a rendered error that quotes the input, such as one from a conversion step
+    /// or the TOML parse error in `SectionError::Parse`, is not passed through it.
+    ///
+    /// ```rust
+    /// use magic_migrate::TryMigrate;
+    /// use serde::Deserialize;
+    /// use std::borrow::Cow;
+    ///
+    /// #[derive(Deserialize, Debug)]
+    /// #[serde(deny_unknown_fields)]
+    /// struct TokenV1 {
+    ///     token: String,
+    ///     expires: u64,
+    /// }
+    ///
+    /// impl TryMigrate for TokenV1 {
+    ///     type TryFrom = Self;
+    ///     type Error = std::convert::Infallible;
+    ///
+    ///     fn deserializer<'de>(input: &str) -> impl serde::de::Deserializer<'de> {
+    ///         toml::Deserializer::new(input)
+    ///     }
+    ///
+    ///     fn redact_input(input: &str) -> Cow<'_, str> {
+    ///         let lines = input.lines().map(|line| {
+    ///             if line.trim_start().starts_with("token") {
+    ///                 "token = [REDACTED]"
+    ///             } else {
+    ///                 line
+    ///             }
+    ///         });
+    ///         Cow::Owned(lines.collect::<Vec<_>>().join("\n"))
+    ///     }
+    /// }
+    ///
+    /// let error = TokenV1::try_from_str_migrations_with_snippet("token = 'secret'\nexpires = 'soon'", 100)
+    ///     .unwrap_err();
+    /// assert_eq!(error.snippet, "token = [REDACTED]\nexpires = 'soon'");
+    /// assert!(!error.to_string().contains("secret"));
+    /// ```
+    fn redact_input(input: &str) -> Cow<'_, str> {
+        if TypeId::of::<Self>() == TypeId::of::<Self::TryFrom>() {
+            Cow::Borrowed(input)
+        } else {
+            <Self::TryFrom as TryMigrate>::redact_input(input)
+        }
+    }
+
     #[must_use]
     fn try_from_str_migrations(input: &str) -> Option<Result<Self, <Self as TryMigrate>::Error>> {
-        if let Ok(instance) = Self::deserialize(Self::deserializer(input)) {
+        // Input that is already the latest version is a single parse
+        let attempt = Self::deserialize(Self::deserializer(input));
+        events::attempt(Self::version_name(), attempt.is_ok());
+        if let Ok(instance) = attempt {
             Some(Ok(instance))
         } else if TypeId::of::<Self>() == TypeId::of::<Self::TryFrom>() {
-            return None;
+            None
         } else {
-            <Self::TryFrom as TryMigrate>::try_from_str_migrations(input).map(|inner| {
-                inner
-                    .map_err(Into::into)
-                    .and_then(|before: <Self as TryMigrate>::TryFrom| {
-                        Self::try_from(before).map_err(Into::into)
-                    })
+            <Self::TryFrom as TryMigrate>::try_from_str_migrations(input)
+                .map(|inner| inner.map_err(Into::into).and_then(walk::step::<Self>))
+        }
+    }
+
+    /// Same as [`TryMigrate::try_from_str_migrations`] but for raw bytes, which are passed
+    /// through [`TryMigrate::preprocess`] first
+    ///
+    /// Returns `None` when the preprocessed bytes are not valid UTF-8.
+    #[must_use]
+    fn try_from_slice_migrations(
+        input: &[u8],
+    ) -> Option<Result<Self, <Self as TryMigrate>::Error>> {
+        match Self::preprocess(input) {
+            Ok(bytes) => Self::try_from_str_migrations(std::str::from_utf8(&bytes).ok()?),
+            Err(error) => Some(Err(error)),
+        }
+    }
+
+    /// Same as [`TryMigrate::try_from_str_migrations`] but also returns the
+    /// [`TryMigrate::version_name`] of the struct that the input was deserialized into
+    ///
+    /// ```rust
+    /// use magic_migrate::TryMigrate;
+    #[doc = include_str!("fixtures/try_personV1_V2.txt")]
+    ///
+    /// magic_migrate::try_migrate_toml_chain!(
+    ///     error: PersonMigrationError,
+    ///     chain: [PersonV1, PersonV2],
+    /// );
+    ///
+    /// let (version, result) =
+    ///     PersonV2::try_from_str_migrations_with_version("name = 'Schneems'\ntitle = 'Dev'")
+    ///         .unwrap();
+    /// assert_eq!(version, "PersonV1");
+    /// assert_eq!(result.unwrap().job_title, "Dev".to_string());
+    /// ```
+    #[must_use]
+    fn try_from_str_migrations_with_version(
+        input: &str,
+    ) -> Option<(&'static str, Result<Self, <Self as TryMigrate>::Error>)> {
+        walk::walk::<Self>(input).map(|(matched, result)| (matched.name, result))
+    }
+
+    /// Same as [`TryMigrate::try_from_str_migrations`] but also returns a [`Provenance`] that
+    /// records which struct the input was deserialized into and when it was migrated
+    ///
+    /// ```rust
+    /// use magic_migrate::TryMigrate;
+    #[doc = include_str!("fixtures/try_personV1_V2.txt")]
+    ///
+    /// magic_migrate::try_migrate_toml_chain!(
+    ///     error: PersonMigrationError,
+    ///     chain: [PersonV1, PersonV2],
+    /// );
+    ///
+    /// let (person, provenance) =
+    ///     PersonV2::try_from_str_migrations_with_provenance("name = 'Schneems'\ntitle = 'Dev'")
+    ///         .unwrap()
+    ///         .unwrap();
+    /// assert_eq!(person.job_title, "Dev".to_string());
+    /// assert_eq!(provenance.from, "PersonV1");
+    /// assert_eq!(provenance.to, "PersonV2");
+    /// assert!(provenance.was_migrated());
+    ///
+    /// let sidecar = toml::to_string(&provenance).unwrap();
+    /// assert!(sidecar.starts_with("from = \"PersonV1\"\nto = \"PersonV2\"\n"));
+    /// ```
+    #[must_use]
+    fn try_from_str_migrations_with_provenance(
+        input: &str,
+    ) -> Option<Result<(Self, Provenance), <Self as TryMigrate>::Error>> {
+        walk::walk::<Self>(input).map(|(matched, result)| {
+            result.map(|value| {
+                let provenance = Provenance {
+                    from: matched.name,
+                    from_since: matched.since,
+                    to: Self::version_name(),
+                    migrated_at: std::time::SystemTime::now(),
+                };
+                (value, provenance)
+            })
+        })
+    }
+
+    /// Same as [`TryMigrate::try_from_str_migrations`] but also returns the
+    /// [`MigrationWarning`]s that conversion steps emitted, oldest step first
+    ///
+    /// Use it for steps that intentionally drop or coerce data and want to report it
+    /// without failing.
+    ///
+    /// ```rust
+    /// use magic_migrate::{MigrationWarning, TryMigrate};
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize, Debug)]
+    /// #[serde(deny_unknown_fields)]
+    /// struct PersonV1 {
+    ///     name: String,
+    /// }
+    ///
+    /// #[derive(Deserialize, Debug)]
+    /// #[serde(deny_unknown_fields)]
+    /// struct PersonV2 {
+    ///     short_name: String,
+    /// }
+    ///
+    /// impl From<PersonV1> for PersonV2 {
+    ///     fn from(value: PersonV1) -> Self {
+    ///         let mut short_name = value.name;
+    ///         if short_name.len() > 4 {
+    ///             MigrationWarning::emit(format!("truncated name `{short_name}`"));
+    ///             short_name.truncate(4);
+    ///         }
+    ///         PersonV2 { short_name }
+    ///     }
+    /// }
+    ///
+    /// magic_migrate::try_migrate_toml_chain!(chain: [PersonV1, PersonV2]);
+    ///
+    /// let (person, warnings) =
+    ///     PersonV2::try_from_str_migrations_with_warnings("name = 'Schneems'")
+    ///         .unwrap()
+    ///         .unwrap();
+    /// assert_eq!(person.short_name, "Schn".to_string());
+    /// assert_eq!(warnings.len(), 1);
+    /// assert_eq!(warnings[0].version, "PersonV2");
+    /// assert_eq!(warnings[0].to_string(), "`PersonV2`: truncated name `Schneems`");
+    /// ```
+    #[must_use]
+    fn try_from_str_migrations_with_warnings(input: &str) -> Option<warnings::Warned<Self>> {
+        let (walked, warnings) = warnings::collect(|| walk::walk::<Self>(input));
+        walked.map(|(_, result)| result.map(|value| (value, warnings)))
+    }
+
+    /// Serialize to TOML with a leading `# magic_migrate: <version_name>` comment so
+    /// [`TryMigrate::try_from_str_migrations_hinted`] can skip straight to this struct
+    ///
+    /// ```rust
+    /// use magic_migrate::TryMigrate;
+    #[doc = include_str!("fixtures/try_personV1_V2.txt")]
+    ///
+    /// magic_migrate::try_migrate_toml_chain!(
+    ///     error: PersonMigrationError,
+    ///     chain: [PersonV1, PersonV2],
+    /// );
+    ///
+    /// let person = PersonV2 {
+    ///     name: "Schneems".to_string(),
+    ///     job_title: "Dev".to_string(),
+    /// };
+    /// let toml = person.to_toml_string_hinted().unwrap();
+    /// assert!(toml.starts_with("# magic_migrate: PersonV2\n"));
+    ///
+    /// let person = PersonV2::try_from_str_migrations_hinted(&toml).unwrap().unwrap();
+    /// assert_eq!(person.job_title, "Dev".to_string());
+    /// ```
+    #[cfg(feature = "toml")]
+    fn to_toml_string_hinted(&self) -> Result<String, toml::ser::Error>
+    where
+        Self: serde::Serialize,
+    {
+        Ok(format!(
+            "{}{}\n{}",
+            walk::HINT_PREFIX,
+            Self::version_name(),
+            toml::to_string(self)?
+        ))
+    }
+
+    /// Same as [`TryMigrate::try_from_str_migrations`] but when the input starts with a
+    /// `# magic_migrate: <version_name>` comment, such as one written by
+    /// `TryMigrate::to_toml_string_hinted` (`toml` feature), only that struct is tried
+    ///
+    /// Falls back to trying every struct in the chain when the comment is missing, names a
+    /// struct that is not in the chain, or names a struct that the input does not match.
+    ///
+    /// ```rust
+    /// use magic_migrate::TryMigrate;
+    #[doc = include_str!("fixtures/try_personV1_V2.txt")]
+    ///
+    /// magic_migrate::try_migrate_toml_chain!(
+    ///     error: PersonMigrationError,
+    ///     chain: [PersonV1, PersonV2],
+    /// );
+    ///
+    /// let person = PersonV2::try_from_str_migrations_hinted(
+    ///     "# magic_migrate: PersonV1\nname = 'Schneems'\ntitle = 'Dev'"
+    /// )
+    /// .unwrap()
+    /// .unwrap();
+    /// assert_eq!(person.job_title, "Dev".to_string());
+    ///
+    /// // Wrong hint
+    /// let person = PersonV2::try_from_str_migrations_hinted(
+    ///     "# magic_migrate: PersonV2\nname = 'Schneems'\ntitle = 'Dev'"
+    /// )
+    /// .unwrap()
+    /// .unwrap();
+    /// assert_eq!(person.job_title, "Dev".to_string());
+    /// ```
+    #[must_use]
+    fn try_from_str_migrations_hinted(
+        input: &str,
+    ) -> Option<Result<Self, <Self as TryMigrate>::Error>> {
+        walk::hint(input)
+            .and_then(|name| walk::walk_to::<Self>(input, &|matched| matched.name == name))
+            .or_else(|| {
+                events::fallback(
+                    Self::version_name(),
+                    "the version hint is missing or does not match",
+                );
+                walk::walk::<Self>(input)
             })
+            .map(|(_, result)| result)
+    }
+
+    /// Find the struct the input deserializes into and the conversions that would migrate it,
+    /// without running any of them
+    ///
+    /// Only deserialization is attempted, no [`TryFrom`] conversions are called. Returns
+    /// `None` when no struct in the chain matches.
+    ///
+    /// ```rust
+    /// use magic_migrate::TryMigrate;
+    #[doc = include_str!("fixtures/try_personV1_V2.txt")]
+    ///
+    /// magic_migrate::try_migrate_toml_chain!(
+    ///     error: PersonMigrationError,
+    ///     chain: [PersonV1, PersonV2],
+    /// );
+    ///
+    /// // Would fail to convert, the plan doesn't know
+    /// let plan = PersonV2::plan("name = 'Schneems'").unwrap();
+    /// assert_eq!(plan.matched, "PersonV1");
+    /// assert_eq!(plan.steps, vec!["PersonV2"]);
+    /// assert_eq!(plan.to_string(), "`PersonV1` => `PersonV2`");
+    ///
+    /// let plan = PersonV2::plan("name = 'Schneems'\njob_title = 'Dev'").unwrap();
+    /// assert!(plan.is_latest());
+    /// ```
+    #[must_use]
+    fn plan(input: &str) -> Option<Plan> {
+        let links = walk::links::<Self>();
+        let index = links
+            .iter()
+            .rposition(|link| (link.deserialize)(input).is_some())?;
+        Some(Plan {
+            matched: links[index].name,
+            steps: links[index + 1..].iter().map(|link| link.name).collect(),
+        })
+    }
+
+    /// The version name of the newest struct in the chain that `input` deserializes into,
+    /// without migrating it
+    ///
+    /// ```rust
+    /// use magic_migrate::TryMigrate;
+    #[doc = include_str!("fixtures/try_personV1_V2.txt")]
+    ///
+    /// magic_migrate::try_migrate_toml_chain!(
+    ///     error: PersonMigrationError,
+    ///     chain: [PersonV1, PersonV2],
+    /// );
+    ///
+    /// // Detected even though migrating it would fail
+    /// assert_eq!(PersonV2::detect_version("name = 'Schneems'"), Some("PersonV1"));
+    /// assert_eq!(PersonV2::detect_version("name = 'Schneems'\njob_title = 'Dev'"), Some("PersonV2"));
+    /// assert_eq!(PersonV2::detect_version("unknown = true"), None);
+    /// ```
+    #[must_use]
+    fn detect_version(input: &str) -> Option<&'static str> {
+        walk::links::<Self>()
+            .iter()
+            .rev()
+            .find(|link| (link.deserialize)(input).is_some())
+            .map(|link| link.name)
+    }
+
+    /// Count how many of `inputs` deserialize into each struct in the chain, without
+    /// migrating any of them
+    ///
+    /// ```rust
+    /// use magic_migrate::TryMigrate;
+    #[doc = include_str!("fixtures/try_personV1_V2.txt")]
+    ///
+    /// magic_migrate::try_migrate_toml_chain!(
+    ///     error: PersonMigrationError,
+    ///     chain: [PersonV1, PersonV2],
+    /// );
+    ///
+    /// let census = PersonV2::version_census([
+    ///     "name = 'Schneems'\njob_title = 'Dev'",
+    ///     "name = 'Richard'\njob_title = 'Ops'",
+    ///     "name = 'Schneems'\ntitle = 'Dev'",
+    ///     "unknown = true",
+    /// ]);
+    /// assert_eq!(census.count("PersonV2"), 2);
+    /// assert_eq!(census.count("PersonV1"), 1);
+    /// assert_eq!(census.to_string(), "2 on `PersonV2`, 1 on `PersonV1`, 1 unreadable");
+    /// ```
+    #[must_use]
+    fn version_census<I>(inputs: I) -> Census
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let links = walk::links::<Self>();
+        let mut census = Census {
+            versions: links.iter().rev().map(|link| (link.name, 0)).collect(),
+            unreadable: 0,
+        };
+        for input in inputs {
+            let input = input.as_ref();
+            match links
+                .iter()
+                .rev()
+                .position(|link| (link.deserialize)(input).is_some())
+            {
+                Some(index) => census.versions[index].1 += 1,
+                None => census.unreadable += 1,
+            }
+        }
+        census
+    }
+
+    /// Same as [`TryMigrate::try_from_str_migrations`] but returns a [`MigrateError`] instead
+    /// of `None` when no struct matches, so the result works with `?`
+    ///
+    /// ```rust
+    /// use magic_migrate::{MigrateError, TryMigrate};
+    #[doc = include_str!("fixtures/try_personV1_V2.txt")]
+    ///
+    /// magic_migrate::try_migrate_toml_chain!(
+    ///     error: PersonMigrationError,
+    ///     chain: [PersonV1, PersonV2],
+    /// );
+    ///
+    /// fn load(input: &str) -> Result<PersonV2, Box<dyn std::error::Error>> {
+    ///     Ok(PersonV2::try_from_str_migrations_result(input)?)
+    /// }
+    ///
+    /// assert_eq!(load("name = 'Schneems'\ntitle = 'Dev'").unwrap().job_title, "Dev".to_string());
+    /// assert_eq!(
+    ///     load("name = 'Schneems'").unwrap_err().to_string(),
+    ///     "Could not migrate from `PersonV1`: Title cannot be empty!!!"
+    /// );
+    ///
+    /// let result = PersonV2::try_from_str_migrations_result("unknown = true");
+    /// assert!(matches!(result, Err(MigrateError::NoMatchingVersion)));
+    /// ```
+    fn try_from_str_migrations_result(
+        input: &str,
+    ) -> Result<Self, MigrateError<<Self as TryMigrate>::Error>> {
+        let (matched, result) = walk::walk::<Self>(input).ok_or(MigrateError::NoMatchingVersion)?;
+        result.map_err(|error| MigrateError::Migrate {
+            version: matched.name,
+            error,
+            help: matched.help,
+        })
+    }
+
+    /// Same as [`TryMigrate::try_from_str_migrations_result`] but tries each of `inputs` in
+    /// order, returns the first one that migrates along with its index
+    ///
+    /// Use it while data moves from one location to another. When no input migrates there
+    /// is one error for each input, in order. See `load_config_sources()` for files.
+    ///
+    /// ```rust
+    /// use magic_migrate::{MigrateErrorKind, TryMigrate};
+    #[doc = include_str!("fixtures/try_personV1_V2.txt")]
+    ///
+    /// magic_migrate::try_migrate_toml_chain!(
+    ///     error: PersonMigrationError,
+    ///     chain: [PersonV1, PersonV2],
+    /// );
+    ///
+    /// let (index, person) =
+    ///     PersonV2::try_from_sources_migrations(["unknown = true", "name = 'Schneems'\ntitle = 'Dev'"])
+    ///         .unwrap();
+    /// assert_eq!(index, 1);
+    /// assert_eq!(person.job_title, "Dev".to_string());
+    ///
+    /// let errors = PersonV2::try_from_sources_migrations(["unknown = true", "name = 'Schneems'"])
+    ///     .unwrap_err();
+    /// let kinds = errors.iter().map(|error| error.kind()).collect::<Vec<_>>();
+    /// assert_eq!(kinds, vec![MigrateErrorKind::NoMatchingVersion, MigrateErrorKind::Migrate]);
+    /// ```
+    fn try_from_sources_migrations<'a>(
+        inputs: impl IntoIterator<Item = &'a str>,
+    ) -> Result<(usize, Self), Vec<MigrateError<<Self as TryMigrate>::Error>>> {
+        let mut errors = Vec::new();
+        for (index, input) in inputs.into_iter().enumerate() {
+            match Self::try_from_str_migrations_result(input) {
+                Ok(latest) => return Ok((index, latest)),
+                Err(error) => errors.push(error),
+            }
+        }
+        Err(errors)
+    }
+
+    /// Same as [`TryMigrate::try_from_str_migrations_result`] but the error includes up to
+    /// `max_chars` characters from the start of the input
+    ///
+    /// Opt in when failures need to be reproduced from logs. The input is passed through
+    /// [`TryMigrate::redact_input`] first.
+    ///
+    /// ```rust
+    /// use magic_migrate::{MigrateErrorKind, TryMigrate};
+    #[doc = include_str!("fixtures/try_personV1_V2.txt")]
+    ///
+    /// magic_migrate::try_migrate_toml_chain!(
+    ///     error: PersonMigrationError,
+    ///     chain: [PersonV1, PersonV2],
+    /// );
+    ///
+    /// let error = PersonV2::try_from_str_migrations_with_snippet("name = 'Schneems'", 10).unwrap_err();
+    /// assert_eq!(error.kind(), MigrateErrorKind::Migrate);
+    /// assert_eq!(error.snippet, "name = 'Sc");
+    /// assert!(error.is_truncated());
+    /// assert_eq!(
+    ///     error.to_string(),
+    ///     r#"Could not migrate from `PersonV1`: Title cannot be empty!!! (input of 17 bytes: "name = 'Sc"...)"#
+    /// );
+    /// ```
+    fn try_from_str_migrations_with_snippet(
+        input: &str,
+        max_chars: usize,
+    ) -> Result<Self, WithSnippet<MigrateError<<Self as TryMigrate>::Error>>> {
+        Self::try_from_str_migrations_result(input)
+            .map_err(|error| WithSnippet::new(error, &Self::redact_input(input), max_chars))
+    }
+
+    /// Same as [`TryMigrate::try_from_str_migrations_result`] but an [`EmptyInputPolicy`]
+    /// controls what happens with input that is empty or only whitespace
+    ///
+    /// ```rust
+    /// use magic_migrate::{EmptyInputPolicy, MigrateError, TryMigrate};
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize, Debug)]
+    /// struct Settings {
+    ///     verbose: Option<bool>,
+    /// }
+    ///
+    /// magic_migrate::migrate_toml_chain!(Settings);
+    ///
+    /// // Every field is optional so empty input deserializes
+    /// let result = Settings::try_from_str_migrations_with_empty(" \n", EmptyInputPolicy::Deserialize);
+    /// assert!(result.unwrap().verbose.is_none());
+    ///
+    /// let result = Settings::try_from_str_migrations_with_empty(" \n", EmptyInputPolicy::Reject);
+    /// assert!(matches!(result, Err(MigrateError::EmptyInput)));
+    /// ```
+    fn try_from_str_migrations_with_empty(
+        input: &str,
+        policy: EmptyInputPolicy,
+    ) -> Result<Self, MigrateError<<Self as TryMigrate>::Error>> {
+        match policy {
+            EmptyInputPolicy::Reject if input.trim().is_empty() => Err(MigrateError::EmptyInput),
+            _ => Self::try_from_str_migrations_result(input),
+        }
+    }
+
+    /// Same as [`TryMigrate::try_from_str_migrations_result`] but only the newest `limit`
+    /// structs, counting this one, are tried
+    ///
+    /// Use it to reject data that is too old quickly instead of parsing it as every older
+    /// layout. Returns [`MigrateError::TooOld`] when none of them match and the chain has
+    /// older structs that were not tried.
+    ///
+    /// ```rust
+    /// use magic_migrate::{MigrateError, TryMigrate};
+    #[doc = include_str!("fixtures/try_personV1_V2.txt")]
+    ///
+    /// magic_migrate::try_migrate_toml_chain!(
+    ///     error: PersonMigrationError,
+    ///     chain: [PersonV1, PersonV2],
+    /// );
+    ///
+    /// let person = PersonV2::try_from_str_migrations_with_limit("name = 'Schneems'\njob_title = 'Dev'", 1)
+    ///     .unwrap();
+    /// assert_eq!(person.job_title, "Dev".to_string());
+    ///
+    /// let result = PersonV2::try_from_str_migrations_with_limit("name = 'Schneems'\ntitle = 'Dev'", 1);
+    /// assert!(matches!(result, Err(MigrateError::TooOld { limit: 1 })));
+    ///
+    /// let result = PersonV2::try_from_str_migrations_with_limit("unknown = true", 2);
+    /// assert!(matches!(result, Err(MigrateError::NoMatchingVersion)));
+    /// ```
+    fn try_from_str_migrations_with_limit(
+        input: &str,
+        limit: usize,
+    ) -> Result<Self, MigrateError<<Self as TryMigrate>::Error>> {
+        let Some((matched, result)) = walk::walk_within::<Self>(input, limit) else {
+            return if walk::links::<Self>().len() > limit {
+                Err(MigrateError::TooOld { limit })
+            } else {
+                Err(MigrateError::NoMatchingVersion)
+            };
+        };
+        result.map_err(|error| MigrateError::Migrate {
+            version: matched.name,
+            error,
+            help: matched.help,
+        })
+    }
+
+    /// Same as [`TryMigrate::try_from_str_migrations_result`] but a panic in a conversion
+    /// step is returned as [`MigrateError::StepPanicked`] instead of unwinding
+    ///
+    /// The panic hook still runs, so the default hook prints the panic to stderr. Panics
+    /// can't be caught when the crate is built with `panic = "abort"`.
+    ///
+    /// ```rust
+    /// use magic_migrate::{MigrateError, TryMigrate};
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize, Debug)]
+    /// #[serde(deny_unknown_fields)]
+    /// struct PersonV1 {
+    ///     name: String,
+    /// }
+    ///
+    /// #[derive(Deserialize, Debug)]
+    /// #[serde(deny_unknown_fields)]
+    /// struct PersonV2 {
+    ///     name: String,
+    ///     initial: char,
+    /// }
+    ///
+    /// impl From<PersonV1> for PersonV2 {
+    ///     fn from(value: PersonV1) -> Self {
+    ///         let initial = value.name.chars().next().expect("name is not empty");
+    ///         PersonV2 { name: value.name, initial }
+    ///     }
+    /// }
+    ///
+    /// #[derive(Debug, thiserror::Error)]
+    /// enum PersonError {}
+    ///
+    /// magic_migrate::try_migrate_toml_chain!(error: PersonError, chain: [PersonV1, PersonV2]);
+    ///
+    /// let person = PersonV2::try_from_str_migrations_catch_unwind("name = 'Schneems'").unwrap();
+    /// assert_eq!(person.initial, 'S');
+    ///
+    /// let result = PersonV2::try_from_str_migrations_catch_unwind("name = ''");
+    /// assert!(matches!(
+    ///     result,
+    ///     Err(MigrateError::StepPanicked { version: "PersonV2", message })
+    ///         if message == "name is not empty"
+    /// ));
+    /// ```
+    fn try_from_str_migrations_catch_unwind(
+        input: &str,
+    ) -> Result<Self, MigrateError<<Self as TryMigrate>::Error>> {
+        walk::walk_catching::<Self>(input)
+            .ok_or(MigrateError::NoMatchingVersion)?
+            .1
+    }
+
+    /// Same as [`TryMigrate::try_from_str_migrations`] but deserializes the input into up
+    /// to `parallelism` structs at the same time
+    ///
+    /// For large inputs where parsing dominates. Structs are tried newest first in batches
+    /// of `parallelism`, each on its own scoped thread, and the newest match wins like it does
+    /// when they are tried one after the other.
+    ///
+    /// The matching struct is parsed twice. Threads only report whether their struct
+    /// matched, the structs in a chain aren't required to be [`Send`], so the match is
+    /// deserialized again on the calling thread to migrate it. That extra parse is the cost
+    /// when the newest struct matches, an older match saves the parses of the newer structs
+    /// that are tried in the same batch. `cargo bench` compares both on a large input.
+    ///
+    /// A panic while deserializing counts as not matching.
+    ///
+    /// ```rust
+    /// use magic_migrate::TryMigrate;
+    #[doc = include_str!("fixtures/try_personV1_V2.txt")]
+    ///
+    /// magic_migrate::try_migrate_toml_chain!(
+    ///     error: PersonMigrationError,
+    ///     chain: [PersonV1, PersonV2],
+    /// );
+    ///
+    /// let person = PersonV2::try_from_str_migrations_concurrent("name = 'Schneems'\ntitle = 'Dev'", 2)
+    ///     .unwrap()
+    ///     .unwrap();
+    /// assert_eq!(person.job_title, "Dev".to_string());
+    ///
+    /// assert!(PersonV2::try_from_str_migrations_concurrent("unknown = true", 2).is_none());
+    /// ```
+    #[must_use]
+    fn try_from_str_migrations_concurrent(
+        input: &str,
+        parallelism: usize,
+    ) -> Option<Result<Self, <Self as TryMigrate>::Error>> {
+        let type_id = walk::newest_match_concurrent::<Self>(input, parallelism)?;
+        walk::walk_to::<Self>(input, &|matched| matched.type_id == type_id)
+            .map(|(_, result)| result)
+    }
+
+    /// Same as [`TryMigrate::try_from_str_migrations`] but a [`RecoveryPolicy`] controls
+    /// what happens when a conversion step fails
+    ///
+    /// ```rust
+    /// use magic_migrate::{RecoveryPolicy, TryMigrate};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Deserialize, Serialize, Debug)]
+    /// struct PersonV1 {
+    ///     name: String,
+    /// }
+    ///
+    /// #[derive(Deserialize, Serialize, Debug)]
+    /// struct PersonV2 {
+    ///     name: String,
+    ///     nickname: Option<String>,
+    /// }
+    ///
+    /// #[derive(Deserialize, Serialize, Debug)]
+    /// struct PersonV3 {
+    ///     name: String,
+    ///     nickname: String,
+    /// }
+    ///
+    /// impl From<PersonV1> for PersonV2 {
+    ///     fn from(value: PersonV1) -> Self {
+    ///         PersonV2 { name: value.name.clone(), nickname: Some(value.name) }
+    ///     }
+    /// }
+    ///
+    /// impl TryFrom<PersonV2> for PersonV3 {
+    ///     type Error = NicknameMissing;
+    ///
+    ///     fn try_from(value: PersonV2) -> Result<Self, NicknameMissing> {
+    ///         let nickname = value.nickname.ok_or(NicknameMissing)?;
+    ///         Ok(PersonV3 { name: value.name, nickname })
+    ///     }
+    /// }
+    ///
+    /// #[derive(Debug, thiserror::Error)]
+    /// #[error("Nickname missing")]
+    /// struct NicknameMissing;
+    ///
+    /// #[derive(Debug, thiserror::Error)]
+    /// enum PersonError {
+    ///     #[error(transparent)]
+    ///     NicknameMissing(#[from] NicknameMissing),
+    /// }
+    ///
+    /// magic_migrate::try_migrate_toml_chain!(
+    ///     error: PersonError,
+    ///     chain: [PersonV1, PersonV2, PersonV3],
+    /// );
+    ///
+    /// // Matches PersonV2 (ABA) and fails to migrate
+    /// let result = PersonV3::try_from_str_migrations_with_policy(
+    ///     "name = 'Schneems'",
+    ///     RecoveryPolicy::FailFast,
+    /// );
+    /// assert!(result.unwrap().is_err());
+    ///
+    /// // Tries PersonV1 after PersonV2 fails
+    /// let person = PersonV3::try_from_str_migrations_with_policy(
+    ///     "name = 'Schneems'",
+    ///     RecoveryPolicy::ContinueWithOlder,
+    /// )
+    /// .unwrap()
+    /// .unwrap();
+    /// assert_eq!(person.nickname, "Schneems".to_string());
+    /// ```
+    #[must_use]
+    fn try_from_str_migrations_with_policy(
+        input: &str,
+        policy: RecoveryPolicy,
+    ) -> Option<Result<Self, <Self as TryMigrate>::Error>> {
+        let first = walk::walk::<Self>(input)?.1;
+        match (first, policy) {
+            (Ok(instance), _) => Some(Ok(instance)),
+            (Err(error), RecoveryPolicy::FailFast) => Some(Err(error)),
+            (Err(error), RecoveryPolicy::ContinueWithOlder) => Some({
+                events::fallback(
+                    Self::version_name(),
+                    "migrating the newest match failed, trying older structs",
+                );
+                (1..)
+                    .map_while(|skip| walk::walk_skipping::<Self>(input, skip))
+                    .find_map(|(_, result)| result.ok())
+                    .ok_or(error)
+            }),
         }
     }
+
+    /// Same as [`TryMigrate::try_from_str_migrations`] but the structs in the chain are tried
+    /// in the order picked by `strategy`
+    ///
+    /// ```rust
+    /// use magic_migrate::{OldestFirst, TryMigrate};
+    #[doc = include_str!("fixtures/try_personV1_V2.txt")]
+    ///
+    /// magic_migrate::try_migrate_toml_chain!(
+    ///     error: PersonMigrationError,
+    ///     chain: [PersonV1, PersonV2],
+    /// );
+    ///
+    /// let person = PersonV2::try_from_str_migrations_with_strategy(
+    ///     "name = 'Schneems'\njob_title = 'Dev'",
+    ///     &OldestFirst,
+    /// )
+    /// .unwrap()
+    /// .unwrap();
+    /// assert_eq!(person.job_title, "Dev".to_string());
+    /// ```
+    #[must_use]
+    fn try_from_str_migrations_with_strategy<S: ResolutionStrategy + ?Sized>(
+        input: &str,
+        strategy: &S,
+    ) -> Option<Result<Self, <Self as TryMigrate>::Error>> {
+        let versions = walk::links::<Self>()
+            .iter()
+            .rev()
+            .map(|link| Candidate::new(link.name, link.type_id))
+            .collect::<Vec<_>>();
+        strategy
+            .order(input, &versions)
+            .into_iter()
+            .find_map(|version| {
+                walk::walk_to::<Self>(input, &|matched| matched.type_id == version.type_id())
+            })
+            .map(|(_, result)| result)
+    }
+
+    /// Same as [`TryMigrate::try_from_str_migrations`] but the input is first checked against
+    /// [`Limits`], use it for untrusted input
+    ///
+    /// ```rust
+    /// use magic_migrate::{LimitExceeded, Limits, TryMigrate};
+    #[doc = include_str!("fixtures/try_personV1_V2.txt")]
+    ///
+    /// magic_migrate::try_migrate_toml_chain!(
+    ///     error: PersonMigrationError,
+    ///     chain: [PersonV1, PersonV2],
+    /// );
+    ///
+    /// let limits = Limits::new().max_bytes(1024).max_depth(8);
+    ///
+    /// let person = PersonV2::try_from_str_migrations_limited("name = 'Schneems'\ntitle = 'Dev'", &limits)
+    ///     .unwrap()
+    ///     .unwrap()
+    ///     .unwrap();
+    /// assert_eq!(person.job_title, "Dev".to_string());
+    ///
+    /// let nested = format!("name = {}{}", "[".repeat(100), "]".repeat(100));
+    /// let result = PersonV2::try_from_str_migrations_limited(&nested, &limits);
+    /// assert_eq!(result.unwrap_err(), LimitExceeded::Depth { limit: 8 });
+    /// ```
+    fn try_from_str_migrations_limited(
+        input: &str,
+        limits: &Limits,
+    ) -> Result<Option<Result<Self, <Self as TryMigrate>::Error>>, LimitExceeded> {
+        limits.check(input)?;
+        Ok(Self::try_from_str_migrations(input))
+    }
+
+    /// Same as [`TryMigrate::try_from_str_migrations`] but the input must have been sealed
+    /// with [`Integrity::seal`], the tag is verified and removed before anything is
+    /// deserialized
+    ///
+    /// ```rust
+    /// use magic_migrate::{Checksum, Integrity, IntegrityError, TryMigrate};
+    #[doc = include_str!("fixtures/try_personV1_V2.txt")]
+    ///
+    /// magic_migrate::try_migrate_toml_chain!(
+    ///     error: PersonMigrationError,
+    ///     chain: [PersonV1, PersonV2],
+    /// );
+    ///
+    /// let sealed = Checksum.seal("name = 'Schneems'\ntitle = 'Dev'");
+    /// let person = PersonV2::try_from_str_migrations_verified(&sealed, &Checksum)
+    ///     .unwrap()
+    ///     .unwrap()
+    ///     .unwrap();
+    /// assert_eq!(person.job_title, "Dev".to_string());
+    ///
+    /// let result = PersonV2::try_from_str_migrations_verified(&sealed.replace("Dev", "Ops"), &Checksum);
+    /// assert!(matches!(result, Err(IntegrityError::Mismatch)));
+    /// ```
+    fn try_from_str_migrations_verified<I: Integrity + ?Sized>(
+        input: &str,
+        integrity: &I,
+    ) -> Result<Option<Result<Self, <Self as TryMigrate>::Error>>, IntegrityError> {
+        let data = integrity.open(input)?;
+        Ok(Self::try_from_str_migrations(data))
+    }
+
+    /// Same as [`TryMigrate::try_from_str_migrations`] but when a conversion step fails the
+    /// struct that the input was deserialized into is returned alongside the error
+    ///
+    /// The struct is type erased, use [`Salvaged::downcast`] to get it back. The input is
+    /// deserialized a second time to get it, when that fails there is no struct to salvage
+    /// but the error is still returned.
+    ///
+    /// ```rust
+    /// use magic_migrate::TryMigrate;
+    #[doc = include_str!("fixtures/try_personV1_V2.txt")]
+    ///
+    /// magic_migrate::try_migrate_toml_chain!(
+    ///     error: PersonMigrationError,
+    ///     chain: [PersonV1, PersonV2],
+    /// );
+    ///
+    /// // Conversion fails (missing title)
+    /// let salvaged = PersonV2::try_from_str_migrations_salvage("name = 'Schneems'")
+    ///     .unwrap()
+    ///     .unwrap_err();
+    ///
+    /// assert_eq!(salvaged.error, PersonMigrationError::TitleCannotBeEmpty);
+    /// assert_eq!(salvaged.version, "PersonV1");
+    ///
+    /// // Keep the name
+    /// let person: PersonV1 = salvaged.downcast().unwrap();
+    /// assert_eq!(person.name, "Schneems".to_string());
+    /// ```
+    #[must_use]
+    fn try_from_str_migrations_salvage(
+        input: &str,
+    ) -> Option<Result<Self, Salvaged<<Self as TryMigrate>::Error>>> {
+        walk::walk::<Self>(input).map(|(matched, result)| {
+            // The conversion step consumed the struct, so it's deserialized again
+            result.map_err(|error| Salvaged::new(error, matched.name, (matched.deserialize)(input)))
+        })
+    }
+
+    /// Same as [`TryMigrate::try_from_str_migrations`] but stops migrating at `V`, an earlier
+    /// struct in the chain, for example to support an older consumer
+    ///
+    /// Returns `None` when `V` is not part of the chain or no struct up to `V` matches the input.
+    ///
+    /// ```rust
+    /// use magic_migrate::TryMigrate;
+    #[doc = include_str!("fixtures/try_personV1_V2.txt")]
+    ///
+    /// #[derive(Deserialize, Serialize, Debug)]
+    /// struct PersonV3 {
+    ///     name: String,
+    ///     job_title: String,
+    ///     nickname: Option<String>,
+    /// }
+    ///
+    /// impl From<PersonV2> for PersonV3 {
+    ///     fn from(value: PersonV2) -> Self {
+    ///         PersonV3 { name: value.name, job_title: value.job_title, nickname: None }
+    ///     }
+    /// }
+    ///
+    /// magic_migrate::try_migrate_toml_chain!(
+    ///     error: PersonMigrationError,
+    ///     chain: [PersonV1, PersonV2, PersonV3],
+    /// );
+    ///
+    /// let person: PersonV2 = PersonV3::try_from_str_migrations_to::<PersonV2>("name = 'Schneems'\ntitle = 'Dev'")
+    ///     .unwrap()
+    ///     .unwrap();
+    /// assert_eq!(person.job_title, "Dev".to_string());
+    ///
+    /// // PersonV3 is not before PersonV2
+    /// assert!(PersonV2::try_from_str_migrations_to::<PersonV3>("name = 'Schneems'\ntitle = 'Dev'").is_none());
+    /// ```
+    #[must_use]
+    fn try_from_str_migrations_to<V: TryMigrate>(
+        input: &str,
+    ) -> Option<Result<V, <V as TryMigrate>::Error>> {
+        if walk::contains::<Self>(TypeId::of::<V>()) {
+            V::try_from_str_migrations(input)
+        } else {
+            None
+        }
+    }
+
+    /// Migrate a value of `V`, an earlier struct in the chain, forward to this struct
+    ///
+    /// Runs the same conversion steps as [`TryMigrate::try_from_str_migrations`] for code
+    /// that already holds an older struct, without serializing it first. Returns `None` when
+    /// `V` is not part of the chain.
+    ///
+    /// ```rust
+    /// use magic_migrate::TryMigrate;
+    #[doc = include_str!("fixtures/try_personV1_V2.txt")]
+    ///
+    /// magic_migrate::try_migrate_toml_chain!(
+    ///     error: PersonMigrationError,
+    ///     chain: [PersonV1, PersonV2],
+    /// );
+    ///
+    /// let person = PersonV1 { name: "Schneems".to_string(), title: Some("Dev".to_string()) };
+    /// let person = PersonV2::try_from_migrations(person).unwrap().unwrap();
+    /// assert_eq!(person.job_title, "Dev".to_string());
+    ///
+    /// let person = PersonV1 { name: "Schneems".to_string(), title: None };
+    /// let result = PersonV2::try_from_migrations(person).unwrap();
+    /// assert_eq!(result.unwrap_err(), PersonMigrationError::TitleCannotBeEmpty);
+    ///
+    /// // PersonV2 is not before PersonV1
+    /// let person = PersonV2 { name: "Schneems".to_string(), job_title: "Dev".to_string() };
+    /// assert!(PersonV1::try_from_migrations(person).is_none());
+    /// ```
+    #[must_use]
+    fn try_from_migrations<V: TryMigrate>(
+        value: V,
+    ) -> Option<Result<Self, <Self as TryMigrate>::Error>> {
+        walk::walk_from::<Self>(Box::new(value), TypeId::of::<V>())
+    }
+
+    /// Same as [`TryMigrate::try_from_str_migrations`] but when the input is already the
+    /// latest version, every older struct that also deserializes it is migrated and compared
+    /// to the direct result
+    ///
+    /// When an older struct migrates to a different value `on_divergence` is called with its
+    /// [`TryMigrate::version_name`], the direct result, and the migrated result. This catches
+    /// structs that silently accept newer data (ABA) without changing what is returned.
+    /// Older structs whose conversion fails are not compared.
+    ///
+    /// ```rust
+    /// use magic_migrate::{Migrate, TryMigrate};
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize, Debug, PartialEq)]
+    /// struct PersonV1 {
+    ///     name: String,
+    /// }
+    ///
+    /// #[derive(Deserialize, Debug, PartialEq)]
+    /// struct PersonV2 {
+    ///     name: String,
+    /// }
+    ///
+    /// impl From<PersonV1> for PersonV2 {
+    ///     fn from(value: PersonV1) -> Self {
+    ///         PersonV2 { name: value.name.to_uppercase() }
+    ///     }
+    /// }
+    ///
+    /// magic_migrate::migrate_toml_chain!(PersonV1, PersonV2);
+    ///
+    /// let mut diverged = Vec::new();
+    /// let person = PersonV2::try_from_str_migrations_shadow("name = 'richard'", |version, direct, migrated| {
+    ///     diverged.push(format!("{version}: {} != {}", direct.name, migrated.name));
+    /// })
+    /// .unwrap()
+    /// .unwrap();
+    ///
+    /// assert_eq!(person.name, "richard".to_string());
+    /// assert_eq!(diverged, vec!["PersonV1: richard != RICHARD".to_string()]);
+    /// ```
+    #[must_use]
+    fn try_from_str_migrations_shadow(
+        input: &str,
+        mut on_divergence: impl FnMut(&'static str, &Self, &Self),
+    ) -> Option<Result<Self, <Self as TryMigrate>::Error>>
+    where
+        Self: PartialEq,
+    {
+        let (matched, result) = walk::walk::<Self>(input)?;
+        if let (Ok(direct), true) = (&result, matched.type_id == TypeId::of::<Self>()) {
+            (1..)
+                .map_while(|skip| walk::walk_skipping::<Self>(input, skip))
+                .for_each(|(older, migrated)| match migrated {
+                    Ok(migrated) if &migrated != direct => {
+                        on_divergence(older.name, direct, &migrated);
+                    }
+                    _ => {}
+                });
+        }
+        Some(result)
+    }
+
+    /// Same as [`TryMigrate::try_from_str_migrations`] but every struct in the chain is
+    /// deserialized with the given [`Format`] instead of [`TryMigrate::deserializer`]
+    ///
+    /// Use this when the same chain is loaded from more than one format.
+    ///
+    /// ```rust
+    /// use magic_migrate::TryMigrate;
+    #[doc = include_str!("fixtures/try_personV1_V2.txt")]
+    ///
+    /// magic_migrate::try_migrate_toml_chain!(
+    ///     error: PersonMigrationError,
+    ///     chain: [PersonV1, PersonV2],
+    /// );
+    ///
+    /// // Formats are also built in behind features, i.e. `magic_migrate::Json`
+    /// struct MyToml;
+    ///
+    /// impl magic_migrate::Format for MyToml {
+    ///     fn deserializer<'de>(input: &str) -> impl serde::de::Deserializer<'de> {
+    ///         toml::Deserializer::new(input)
+    ///     }
+    /// }
+    ///
+    /// let person = PersonV2::try_from_str_migrations_as::<MyToml>("name = 'Schneems'\ntitle = 'Dev'")
+    ///     .unwrap()
+    ///     .unwrap();
+    /// assert_eq!(person.job_title, "Dev".to_string());
+    /// ```
+    #[must_use]
+    fn try_from_str_migrations_as<F: Format>(
+        input: &str,
+    ) -> Option<Result<Self, <Self as TryMigrate>::Error>> {
+        walk::walk_as::<Self, F>(input).map(|(_, result)| result)
+    }
+
+    /// A short name for this struct used in output, defaults to the type name without its module path
+    ///
+    /// Set it with `as "name"` in a `try_*` chain macro, see [`try_migrate_deserializer_chain!`].
+    fn version_name() -> &'static str {
+        short_type_name::<Self>()
+    }
+
+    /// The release that introduced this struct, used in output and reports when set
+    ///
+    /// Set it with `since "1.2.3"` in a `try_*` chain macro, see [`try_migrate_deserializer_chain!`].
+    fn since() -> Option<&'static str> {
+        None
+    }
+
+    /// A link to documentation on fixing data of this struct that fails to migrate, included
+    /// in [`MigrateError::Migrate`] and printed output
+    ///
+    /// Set it with `help "https://..."` in a `try_*` chain macro, see
+    /// [`try_migrate_deserializer_chain!`]. Structs without one use the link of the struct
+    /// they migrate from, so setting it on the first struct covers the whole chain.
+    fn help() -> Option<&'static str> {
+        if TypeId::of::<Self>() == TypeId::of::<Self::TryFrom>() {
+            None
+        } else {
+            <Self::TryFrom as TryMigrate>::help()
+        }
+    }
+
+    /// Same as [`TryMigrate::try_from_str_migrations`] but prints what happened to the
+    /// [`bullet_stream`] global writer as a sub-bullet
+    ///
+    /// Nothing is printed when the input is already the latest version.
+    ///
+    /// ```rust
+    /// use magic_migrate::TryMigrate;
+    #[doc = include_str!("fixtures/try_personV1_V2.txt")]
+    ///
+    /// magic_migrate::try_migrate_toml_chain!(
+    ///     error: PersonMigrationError,
+    ///     chain: [PersonV1, PersonV2],
+    /// );
+    ///
+    /// let output = bullet_stream::global::with_locked_writer(Vec::new(), || {
+    ///     let _ = PersonV2::try_from_str_migrations_print("name = 'Schneems'\ntitle = 'Dev'");
+    ///     let _ = PersonV2::try_from_str_migrations_print("name = 'Schneems'");
+    ///     let _ = PersonV2::try_from_str_migrations_print("unknown = true");
+    /// });
+    ///
+    /// assert_eq!(
+    ///     bullet_stream::strip_ansi(String::from_utf8_lossy(&output)),
+    ///     [
+    ///         "  - Migrated `PersonV1` to `PersonV2`",
+    ///         "  - Could not migrate `PersonV1` to `PersonV2`: Title cannot be empty!!!",
+    ///         "  - Could not deserialize any version of `PersonV2`",
+    ///         ""
+    ///     ]
+    ///     .join("\n")
+    /// );
+    /// ```
+    #[cfg(feature = "bullet_stream")]
+    #[must_use]
+    fn try_from_str_migrations_print(
+        input: &str,
+    ) -> Option<Result<Self, <Self as TryMigrate>::Error>> {
+        use bullet_stream::{global::print, style};
+
+        let latest = style::value(Self::version_name());
+        match walk::walk::<Self>(input) {
+            Some((matched, Ok(instance))) => {
+                if matched.type_id != TypeId::of::<Self>() {
+                    print::sub_bullet(format!(
+                        "Migrated {} to {latest}",
+                        style::value(matched.name)
+                    ));
+                }
+                Some(Ok(instance))
+            }
+            Some((matched, Err(error))) => {
+                let help = matched
+                    .help
+                    .map(|help| format!(" (see {})", style::url(help)))
+                    .unwrap_or_default();
+                print::sub_bullet(format!(
+                    "Could not migrate {} to {latest}: {error}{help}",
+                    style::value(matched.name)
+                ));
+                Some(Err(error))
+            }
+            None => {
+                print::sub_bullet(format!("Could not deserialize any version of {latest}"));
+                None
+            }
+        }
+    }
+
+    /// Migrate `input` and report how it differs from the freshly computed `now` value
+    ///
+    /// Returns `None` when no struct in the chain could deserialize the input (same as
+    /// [`TryMigrate::try_from_str_migrations`]). An empty list means the migrated value
+    /// matches `now` and the cache can be kept.
+    ///
+    /// ```rust
+    /// use cache_diff::CacheDiff;
+    /// use magic_migrate::{Migrate, TryMigrate};
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Deserialize, Serialize, Debug)]
+    /// #[serde(deny_unknown_fields)]
+    /// struct MetadataV1 {
+    ///     version: String,
+    /// }
+    ///
+    /// #[derive(Deserialize, Serialize, Debug, CacheDiff)]
+    /// #[serde(deny_unknown_fields)]
+    /// struct MetadataV2 {
+    ///     version: String,
+    ///     arch: String,
+    /// }
+    ///
+    /// impl From<MetadataV1> for MetadataV2 {
+    ///     fn from(value: MetadataV1) -> Self {
+    ///         MetadataV2 {
+    ///             version: value.version,
+    ///             arch: "amd64".to_string(),
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// magic_migrate::migrate_toml_chain!(MetadataV1, MetadataV2);
+    ///
+    /// let now = MetadataV2 {
+    ///     version: "3.4.0".to_string(),
+    ///     arch: "arm64".to_string(),
+    /// };
+    /// let changed = MetadataV2::try_from_str_migrations_diff("version = '3.3.0'", &now)
+    ///     .unwrap()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(
+    ///     changed,
+    ///     vec![
+    ///         "version (`3.3.0` to `3.4.0`)".to_string(),
+    ///         "arch (`amd64` to `arm64`)".to_string()
+    ///     ]
+    /// );
+    /// ```
+    #[cfg(feature = "cache_diff")]
+    #[must_use]
+    fn try_from_str_migrations_diff(
+        input: &str,
+        now: &Self,
+    ) -> Option<Result<Vec<String>, <Self as TryMigrate>::Error>>
+    where
+        Self: cache_diff::CacheDiff,
+    {
+        Self::try_from_str_migrations(input).map(|result| result.map(|old| now.diff(&old)))
+    }
+
+    /// The serialized shape of this version as described by its [`serde::Deserialize`]
+    /// implementation. Field names are always captured, field types are best-effort.
+    ///
+    /// ```rust
+    /// use magic_migrate::TryMigrate;
+    #[doc = include_str!("fixtures/try_personV1_V2.txt")]
+    ///
+    /// magic_migrate::try_migrate_toml_chain!(
+    ///     error: PersonMigrationError,
+    ///     chain: [PersonV1, PersonV2],
+    /// );
+    ///
+    /// assert_eq!(
+    ///     "PersonV1 { name: str, title: option<str> }",
+    ///     PersonV1::schema().to_string()
+    /// );
+    /// ```
+    fn schema() -> Schema {
+        Schema::of::<Self>()
+    }
+
+    /// Check that every struct in the chain uses the same deserializer type as the struct it
+    /// migrates from, unless it sets [`TryMigrate::CHANGES_FORMAT`]
+    ///
+    /// The chain macros always reuse the first struct's deserializer, this catches hand
+    /// written links that don't. It's a runtime check rather than a compile error because
+    /// each [`TryMigrate::deserializer`] returns its own opaque `impl Deserializer` type,
+    /// which the compiler won't compare with another impl's, so call it from a test. The
+    /// types are read from the signatures, no deserializer is made.
+    ///
+    /// ```rust
+    /// use magic_migrate::TryMigrate;
+    #[doc = include_str!("fixtures/try_personV1_V2.txt")]
+    ///
+    /// magic_migrate::try_migrate_toml_chain!(
+    ///     error: PersonMigrationError,
+    ///     chain: [PersonV1],
+    /// );
+    ///
+    /// impl TryMigrate for PersonV2 {
+    ///     type TryFrom = PersonV1;
+    ///     type Error = PersonMigrationError;
+    ///
+    ///     fn deserializer<'de>(input: &str) -> impl serde::de::Deserializer<'de> {
+    ///         toml::de::ValueDeserializer::new(input)
+    ///     }
+    /// }
+    ///
+    /// let mismatch = PersonV2::check_deserializers().unwrap_err();
+    /// assert_eq!(mismatch.version, "PersonV2");
+    /// assert_eq!(mismatch.expected, "toml::de::Deserializer");
+    /// assert_eq!(mismatch.found, "toml::de::ValueDeserializer");
+    /// ```
+    fn check_deserializers() -> Result<(), DeserializerMismatch> {
+        check::check_deserializers::<Self>()
+    }
+
+    /// A markdown table describing every struct in the chain, oldest first
+    ///
+    /// Each row lists the [`TryMigrate::since`] release, the fields added and removed compared
+    /// to the struct before it (a field
+    /// that changed type shows up in both), the error type, and the deserializer type.
+    ///
+    /// ```rust
+    /// use magic_migrate::TryMigrate;
+    #[doc = include_str!("fixtures/try_personV1_V2.txt")]
+    ///
+    /// magic_migrate::try_migrate_toml_chain!(
+    ///     error: PersonMigrationError,
+    ///     chain: [PersonV1, PersonV2],
+    /// );
+    ///
+    /// assert_eq!(
+    ///     PersonV2::chain_docs(),
+    ///     r#"| Version | Since | Added | Removed | Error | Deserializer |
+    /// |---------|-------|-------|---------|-------|--------------|
+    /// | `PersonV1` |  | `name: str`, `title: option<str>` |  | `PersonMigrationError` | `toml::de::Deserializer` |
+    /// | `PersonV2` |  | `job_title: str` | `title: option<str>` | `PersonMigrationError` | `toml::de::Deserializer` |
+    /// "#
+    /// );
+    /// ```
+    fn chain_docs() -> String {
+        docs::chain_docs::<Self>()
+    }
+
+    /// A stable hash of this version's [`TryMigrate::schema`]
+    ///
+    /// Useful as part of a cache key so that editing a struct in place (without adding a
+    /// new version to the chain) still invalidates the cache. The value depends only on the
+    /// serialized struct and field names and types, so it is the same across builds and platforms.
+    ///
+    /// It's best-effort. The schema is recorded by running the `Deserialize` impl against
+    /// placeholder values. A field whose `deserialize_with` or `TryFrom` type rejects or
+    /// panics on every placeholder is recorded as far as it got, and the fingerprint is
+    /// only stable if that impl is deterministic. A panic while recording isn't passed to
+    /// the panic hook, so it isn't printed.
+    ///
+    /// ```rust
+    /// use magic_migrate::TryMigrate;
+    /// use serde::{Deserialize, Deserializer};
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    ///
+    /// fn port<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u16, D::Error> {
+    ///     let port = String::deserialize(deserializer)?;
+    ///     Ok(port.parse().expect("Ports are numbers"))
+    /// }
+    ///
+    /// #[derive(Deserialize, Debug)]
+    /// struct ServerV1 {
+    ///     name: String,
+    ///     #[serde(deserialize_with = "port")]
+    ///     port: u16,
+    /// }
+    ///
+    /// magic_migrate::try_migrate_toml_chain!(chain: [ServerV1]);
+    ///
+    /// static PANICS: AtomicUsize = AtomicUsize::new(0);
+    /// std::panic::set_hook(Box::new(|_| {
+    ///     PANICS.fetch_add(1, Ordering::SeqCst);
+    /// }));
+    ///
+    /// // `port` panics on the placeholder, the panic is caught without calling the hook
+    /// assert_eq!(ServerV1::schema().to_string(), "ServerV1 { name: str, port: str }");
+    /// assert_eq!(ServerV1::schema_fingerprint(), ServerV1::schema_fingerprint());
+    /// assert_eq!(PANICS.load(Ordering::SeqCst), 0);
+    ///
+    /// // The hook is restored afterwards
+    /// let _ = std::panic::catch_unwind(|| panic!("Not a probe"));
+    /// assert_eq!(PANICS.load(Ordering::SeqCst), 1);
+    /// ```
+    fn schema_fingerprint() -> u64 {
+        schema::fnv1a(schema::FNV_OFFSET, Self::schema().to_string().as_bytes())
+    }
+
+    /// A stable hash of every version's [`TryMigrate::schema`] from the start of the chain
+    /// up to and including this one
+    ///
+    /// ```rust
+    /// use magic_migrate::TryMigrate;
+    #[doc = include_str!("fixtures/try_personV1_V2.txt")]
+    ///
+    /// magic_migrate::try_migrate_toml_chain!(
+    ///     error: PersonMigrationError,
+    ///     chain: [PersonV1, PersonV2],
+    /// );
+    ///
+    /// // The first struct in the chain has nothing before it
+    /// assert_eq!(PersonV1::schema_fingerprint(), PersonV1::chain_fingerprint());
+    ///
+    /// // Later structs include everything that came before
+    /// assert_ne!(PersonV2::schema_fingerprint(), PersonV2::chain_fingerprint());
+    /// assert_ne!(PersonV1::chain_fingerprint(), PersonV2::chain_fingerprint());
+    /// ```
+    fn chain_fingerprint() -> u64 {
+        if TypeId::of::<Self>() == TypeId::of::<Self::TryFrom>() {
+            Self::schema_fingerprint()
+        } else {
+            let before = <Self::TryFrom as TryMigrate>::chain_fingerprint();
+            schema::fnv1a(before, Self::schema().to_string().as_bytes())
+        }
+    }
+
+    /// A description of every struct in the chain, oldest first, for external tooling
+    ///
+    /// ```rust
+    /// use magic_migrate::TryMigrate;
+    #[doc = include_str!("fixtures/try_personV1_V2.txt")]
+    ///
+    /// magic_migrate::try_migrate_toml_chain!(
+    ///     error: PersonMigrationError,
+    ///     chain: [PersonV1, PersonV2 since "2.0.0"],
+    /// );
+    ///
+    /// let metadata = PersonV2::chain_metadata();
+    /// let names = metadata.versions.iter().map(|version| version.name).collect::<Vec<_>>();
+    /// assert_eq!(names, vec!["PersonV1", "PersonV2"]);
+    ///
+    /// let latest = &metadata.versions[1];
+    /// assert_eq!(latest.since, Some("2.0.0"));
+    /// assert_eq!(latest.error, "PersonMigrationError");
+    /// assert_eq!(latest.deserializer, "toml::de::Deserializer");
+    /// assert_eq!(latest.schema_fingerprint, PersonV2::schema_fingerprint());
+    /// ```
+    fn chain_metadata() -> ChainMetadata {
+        ChainMetadata::of::<Self>()
+    }
+
+    /// [`TryMigrate::chain_metadata`] serialized as pretty printed JSON
+    ///
+    /// ```rust
+    /// use magic_migrate::TryMigrate;
+    #[doc = include_str!("fixtures/try_personV1_V2.txt")]
+    ///
+    /// magic_migrate::try_migrate_toml_chain!(
+    ///     error: PersonMigrationError,
+    ///     chain: [PersonV1, PersonV2],
+    /// );
+    ///
+    /// let json: serde_json::Value = serde_json::from_str(&PersonV2::chain_metadata_json()).unwrap();
+    /// assert_eq!(json["versions"][0]["name"], "PersonV1");
+    /// assert_eq!(json["versions"][1]["schema"], "PersonV2 { name: str, job_title: str }");
+    /// assert_eq!(
+    ///     json["versions"][1]["chain_fingerprint"],
+    ///     format!("{:016x}", PersonV2::chain_fingerprint())
+    /// );
+    /// ```
+    #[cfg(feature = "json")]
+    fn chain_metadata_json() -> String {
+        serde_json::to_string_pretty(&Self::chain_metadata())
+            .expect("Chain metadata only contains strings and options")
+    }
 }
 
 /// Implement [`TryMigrate`] for all structs that infailably
@@ -231,6 +1828,14 @@ where
     type Error = std::convert::Infallible;
 }
 
+/// The type name without its module path, generic parameters are kept as is
+fn short_type_name<T: ?Sized>() -> &'static str {
+    let name = std::any::type_name::<T>();
+    let end = name.find('<').unwrap_or(name.len());
+    let start = name[..end].rfind("::").map_or(0, |i| i + 2);
+    &name[start..]
+}
+
 /// Macro for linking structs together in an infallible [`Migrate`] migration chain
 /// without defining the first migration in the chain
 /// or the deserializer.
@@ -251,11 +1856,11 @@ macro_rules! migrate_link {
     // Base case, defines the trait
     // Links a single pair i.e. A => B
     ($a:ident, $b:ident) => (
-        impl Migrate for $b {
+        impl $crate::Migrate for $b {
             type From = $a;
 
-            fn deserializer<'de>(input: &str) -> impl serde::de::Deserializer<'de> {
-                <Self as Migrate>::From::deserializer(input)
+            fn deserializer<'de>(input: &str) -> impl $crate::__private::serde::de::Deserializer<'de> {
+                <<Self as $crate::Migrate>::From as $crate::Migrate>::deserializer(input)
             }
         }
     );
@@ -289,20 +1894,8 @@ macro_rules! migrate_link {
 /// ```
 #[macro_export(local_inner_macros)]
 macro_rules! migrate_toml_chain {
-    // Base case
-    // Start of the migration chain
-    // In A => B => C, we must define the A => A case first.
-    ($a:ident) => (
-        $crate::migrate_deserializer_chain!(
-            deserializer: toml::Deserializer::new,
-            chain: [$a]
-        );
-    );
-    ($a:ident, $($rest:ident),+) => (
-        $crate::migrate_deserializer_chain!(
-            deserializer: toml::Deserializer::new,
-            chain: [$a, $($rest),+]
-        );
+    ($($chain:tt)+) => (
+        $crate::__with_toml_deserializer!(migrate_deserializer_chain {} { chain: [$($chain)+] });
     );
 }
 
@@ -325,22 +1918,40 @@ macro_rules! migrate_toml_chain {
 macro_rules! try_migrate_link {
     // Base case, defines the trait
     // Links a single pair i.e. A => B
-    ($a:ident, $b:ident) => (
-        impl TryMigrate for $b {
+    ($a:ident $(as $a_name:literal)? $(since $a_since:literal)? $(help $a_help:literal)?, $b:ident $(as $b_name:literal)? $(since $b_since:literal)? $(help $b_help:literal)?) => (
+        impl $crate::TryMigrate for $b {
             type TryFrom = $a;
-            type Error = <<Self as TryMigrate>::TryFrom as TryMigrate>::Error;
+            type Error = <<Self as $crate::TryMigrate>::TryFrom as $crate::TryMigrate>::Error;
 
-            fn deserializer<'de>(input: &str) -> impl serde::de::Deserializer<'de> {
-                <Self as TryMigrate>::TryFrom::deserializer(input)
+            fn deserializer<'de>(input: &str) -> impl $crate::__private::serde::de::Deserializer<'de> {
+                <<Self as $crate::TryMigrate>::TryFrom as $crate::TryMigrate>::deserializer(input)
             }
+
+            $(
+                fn version_name() -> &'static str {
+                    $b_name
+                }
+            )?
+
+            $(
+                fn since() -> ::std::option::Option<&'static str> {
+                    ::std::option::Option::Some($b_since)
+                }
+            )?
+
+            $(
+                fn help() -> ::std::option::Option<&'static str> {
+                    ::std::option::Option::Some($b_help)
+                }
+            )?
         }
     );
-    ($a:ident, $b:ident, $($rest:ident),+) => (
+    ($a:ident $(as $a_name:literal)? $(since $a_since:literal)? $(help $a_help:literal)?, $b:ident $(as $b_name:literal)? $(since $b_since:literal)? $(help $b_help:literal)?, $($rest:tt)+) => (
         // Call the base case to link A => B
-        $crate::try_migrate_link!($a, $b);
+        $crate::try_migrate_link!($a, $b $(as $b_name)? $(since $b_since)? $(help $b_help)?);
 
         // Link B => C, and the rest
-        $crate::try_migrate_link!($b, $($rest),*);
+        $crate::try_migrate_link!($b $(as $b_name)? $(since $b_since)? $(help $b_help)?, $($rest)+);
     );
 }
 
@@ -380,28 +1991,188 @@ macro_rules! try_migrate_link {
 /// assert!(result.is_err());
 /// assert!(matches!(result, Err(PersonMigrationError::TitleCannotBeEmpty)));
 /// ```
+///
+/// ## Mixing `From` and `TryFrom` links
+///
+/// A link can use [`From`] instead of [`TryFrom`]. The standard library's blanket
+/// `TryFrom` impl uses [`std::convert::Infallible`] as the error, which the chain macros
+/// convert into your error type, so no wrapper `TryFrom` impl is needed.
+///
+/// ```rust
+/// use magic_migrate::TryMigrate;
+#[doc = include_str!("fixtures/try_personV1_V2.txt")]
+///
+/// #[derive(Deserialize, Serialize, Debug)]
+/// struct PersonV3 {
+///     name: String,
+///     job_title: String,
+///     nickname: Option<String>,
+/// }
+///
+/// // Infallible
+/// impl From<PersonV2> for PersonV3 {
+///     fn from(value: PersonV2) -> Self {
+///         PersonV3 { name: value.name, job_title: value.job_title, nickname: None }
+///     }
+/// }
+///
+/// magic_migrate::try_migrate_toml_chain!(
+///     error: PersonMigrationError,
+///     chain: [PersonV1, PersonV2, PersonV3],
+/// );
+///
+/// let person = PersonV3::try_from_str_migrations("name = 'Schneems'\ntitle = 'Dev'")
+///     .unwrap()
+///     .unwrap();
+/// assert_eq!(person.job_title, "Dev".to_string());
+/// ```
+///
+/// ## Without an error
+///
+/// When every link is a [`From`] conversion, `error:` can be left out and the chain's error
+/// is [`std::convert::Infallible`].
+///
+/// ```rust
+/// use magic_migrate::TryMigrate;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize, Debug)]
+/// #[serde(deny_unknown_fields)]
+/// struct PersonV1 {
+///     name: String,
+/// }
+///
+/// #[derive(Deserialize, Debug)]
+/// #[serde(deny_unknown_fields)]
+/// struct PersonV2 {
+///     name: String,
+///     nickname: Option<String>,
+/// }
+///
+/// impl From<PersonV1> for PersonV2 {
+///     fn from(value: PersonV1) -> Self {
+///         PersonV2 { name: value.name, nickname: None }
+///     }
+/// }
+///
+/// magic_migrate::try_migrate_toml_chain!(chain: [PersonV1, PersonV2]);
+///
+/// let person: Result<PersonV2, std::convert::Infallible> =
+///     PersonV2::try_from_str_migrations("name = 'Schneems'").unwrap();
+/// assert_eq!(person.unwrap().name, "Schneems".to_string());
+/// ```
+///
+/// ## Boxed errors
+///
+/// The error can be any type that the links' errors convert into, such as
+/// `Box<dyn std::error::Error + Send + Sync>`. The standard library already converts
+/// [`std::convert::Infallible`] into a boxed error, so the macros only implement that
+/// conversion when the error is a type name like `PersonMigrationError`.
+///
+/// ```rust
+/// use magic_migrate::TryMigrate;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize, Debug)]
+/// #[serde(deny_unknown_fields)]
+/// struct PersonV1 {
+///     name: String,
+///     title: Option<String>,
+/// }
+///
+/// #[derive(Deserialize, Debug)]
+/// #[serde(deny_unknown_fields)]
+/// struct PersonV2 {
+///     name: String,
+///     job_title: String,
+/// }
+///
+/// impl TryFrom<PersonV1> for PersonV2 {
+///     type Error = Box<dyn std::error::Error + Send + Sync>;
+///
+///     fn try_from(value: PersonV1) -> Result<Self, Self::Error> {
+///         let job_title = value.title.ok_or("Title cannot be empty")?;
+///         Ok(PersonV2 { name: value.name, job_title })
+///     }
+/// }
+///
+/// magic_migrate::try_migrate_toml_chain!(
+///     error: Box<dyn std::error::Error + Send + Sync>,
+///     chain: [PersonV1, PersonV2],
+/// );
+///
+/// let person = PersonV2::try_from_str_migrations("name = 'Schneems'\ntitle = 'Dev'")
+///     .unwrap()
+///     .unwrap();
+/// assert_eq!(person.job_title, "Dev".to_string());
+///
+/// let error = PersonV2::try_from_str_migrations("name = 'Schneems'")
+///     .unwrap()
+///     .unwrap_err();
+/// assert_eq!(error.to_string(), "Title cannot be empty");
+/// ```
+///
+/// ## `toml::Spanned` fields
+///
+/// Fields can be wrapped in `toml::Spanned` to report errors with their location in the
+/// input. A span that is moved from an older struct still points into the original input,
+/// give fields that are created by a conversion an empty span. Spans are not serialized, a
+/// value that is written back (such as with `CacheFile::read_repair`) and read again
+/// gets spans into the new contents.
+///
+/// ```rust
+/// use magic_migrate::TryMigrate;
+/// use serde::Deserialize;
+/// use toml::Spanned;
+///
+/// #[derive(Deserialize, Debug)]
+/// #[serde(deny_unknown_fields)]
+/// struct ConfigV1 {
+///     name: Spanned<String>,
+/// }
+///
+/// #[derive(Deserialize, Debug)]
+/// #[serde(deny_unknown_fields)]
+/// struct ConfigV2 {
+///     name: Spanned<String>,
+///     stack: Spanned<String>,
+/// }
+///
+/// impl From<ConfigV1> for ConfigV2 {
+///     fn from(value: ConfigV1) -> Self {
+///         ConfigV2 { name: value.name, stack: Spanned::new(0..0, "heroku-24".to_string()) }
+///     }
+/// }
+///
+/// #[derive(Debug, thiserror::Error)]
+/// enum ConfigError {}
+///
+/// magic_migrate::try_migrate_toml_chain!(error: ConfigError, chain: [ConfigV1, ConfigV2]);
+///
+/// let input = "name = 'Schneems'";
+/// let config = ConfigV2::try_from_str_migrations(input).unwrap().unwrap();
+/// assert_eq!(&input[config.name.span()], "'Schneems'");
+/// assert_eq!(config.stack.span(), 0..0);
+/// ```
 #[macro_export]
 macro_rules! try_migrate_toml_chain {
-    // Base case
-    (error: $err:ident, chain: [$a:ident] $(,)?) => {
-        $crate::try_migrate_deserializer_chain!(error: $err, deserializer: toml::Deserializer::new, chain: [$a]);
+    (error: $err:ident, chain: [$($chain:tt)+] $(,)?) => {
+        $crate::__with_toml_deserializer!(try_migrate_deserializer_chain { error: $err, } { chain: [$($chain)+] });
     };
-    // Position variant
-    (chain: [$a:ident], error: $err:ident $(,)?) => {
-        $crate::try_migrate_toml_chain!(error: $err, chain: [$a]);
+    (error: $err:ty, chain: [$($chain:tt)+] $(,)?) => {
+        $crate::__with_toml_deserializer!(try_migrate_deserializer_chain { error: $err, } { chain: [$($chain)+] });
+    };
+    // Without an error every link must be infallible
+    (chain: [$($chain:tt)+] $(,)?) => {
+        $crate::__with_toml_deserializer!(try_migrate_deserializer_chain {} { chain: [$($chain)+] });
     };
-    // Rest case
-    (error: $err:ident, chain: [$a:ident, $($rest:ident),+] $(,)?) => (
-        // Call the base case to link A => A
-        $crate::try_migrate_toml_chain!(error: $err, chain: [$a]);
-
-        // Link the rest i.e. A => B, B => C, etc.
-        $crate::try_migrate_link!($a, $($rest),+);
-    );
     // Position variant
-    (chain: [$a:ident, $($rest:ident),+], error: $err:ident $(,)?) => (
-        $crate::try_migrate_toml_chain!(error: $err, chain: [$a, $($rest),+]);
-    );
+    (chain: [$($chain:tt)+], error: $err:ident $(,)?) => {
+        $crate::try_migrate_toml_chain!(error: $err, chain: [$($chain)+]);
+    };
+    (chain: [$($chain:tt)+], error: $err:ty $(,)?) => {
+        $crate::try_migrate_toml_chain!(error: $err, chain: [$($chain)+]);
+    };
 }
 
 /// A macro to help define infallible [`Migrate`] based migrations with an arbitrary deserializer.
@@ -436,14 +2207,22 @@ macro_rules! try_migrate_toml_chain {
 macro_rules! migrate_deserializer_chain {
     // Base case
     (deserializer: $deser:path, chain: [$a:ident] $(,)?) => {
-        impl Migrate for $a {
+        impl $crate::Migrate for $a {
             type From = Self;
 
-            fn deserializer<'de>(input: &str) -> impl serde::de::Deserializer<'de> {
+            fn deserializer<'de>(input: &str) -> impl $crate::__private::serde::de::Deserializer<'de> {
                 $deser(input)
             }
         }
     };
+    // Feature gated first struct, the next struct starts the chain when it's compiled out
+    (deserializer: $deser:path, chain: [#[cfg($pred:meta)] $a:ident, $($rest:ident),+] $(,)?) => (
+        #[cfg($pred)]
+        $crate::migrate_deserializer_chain!(deserializer: $deser, chain: [$a, $($rest),+]);
+
+        #[cfg(not($pred))]
+        $crate::migrate_deserializer_chain!(deserializer: $deser, chain: [$($rest),+]);
+    );
     // Rest case
     (deserializer: $deser:path, chain: [$a:ident, $($rest:ident),+] $(,)?) => (
         // Call the base case to link A => A
@@ -453,13 +2232,9 @@ macro_rules! migrate_deserializer_chain {
         $crate::migrate_link!($a, $($rest),+);
     );
 
-    // Base case variants
-    (chain: [$a:ident], deserializer: $deser:path $(,)?) => {
-        $crate::migrate_deserializer_chain!(deserializer: $deser, chain: [$a]);
-    };
-    // Rest case variants
-    (chain: [$a:ident, $($rest:ident),+] , deserializer: $deser:path $(,)?) => {
-        $crate::migrate_deserializer_chain!(deserializer: $deser, chain: [$a, $($rest),+]);
+    // Position variant
+    (chain: [$($chain:tt)+], deserializer: $deser:path $(,)?) => {
+        $crate::migrate_deserializer_chain!(deserializer: $deser, chain: [$($chain)+]);
     };
 }
 
@@ -504,63 +2279,732 @@ macro_rules! migrate_deserializer_chain {
 /// assert!(result.is_err());
 /// assert!(matches!(result, Err(PersonMigrationError::TitleCannotBeEmpty)));
 /// ```
+///
+/// ## Feature gated versions
+///
+/// The first struct in `chain:` can have a `#[cfg(...)]` attribute. When it's compiled out
+/// the chain starts at the next struct instead. The same works for every chain macro.
+///
+/// ```rust
+/// use magic_migrate::TryMigrate;
+/// use serde::Deserialize;
+///
+/// #[cfg(feature = "legacy")]
+/// #[derive(Deserialize, Debug)]
+/// struct PersonV1 {
+///     name: String,
+/// }
+///
+/// #[derive(Deserialize, Debug)]
+/// struct PersonV2 {
+///     full_name: String,
+/// }
+///
+/// #[cfg(feature = "legacy")]
+/// impl From<PersonV1> for PersonV2 {
+///     fn from(value: PersonV1) -> Self {
+///         PersonV2 { full_name: value.name }
+///     }
+/// }
+///
+/// #[derive(Debug, thiserror::Error)]
+/// #[error("Unreachable")]
+/// struct PersonError;
+///
+/// magic_migrate::try_migrate_deserializer_chain!(
+///     deserializer: toml::Deserializer::new,
+///     error: PersonError,
+///     chain: [#[cfg(feature = "legacy")] PersonV1, PersonV2],
+/// );
+///
+/// let person = PersonV2::try_from_str_migrations("full_name = 'Schneems'").unwrap().unwrap();
+/// assert_eq!(person.full_name, "Schneems".to_string());
+///
+/// // `legacy` is not enabled so `PersonV1` is not part of the chain
+/// assert!(PersonV2::try_from_str_migrations("name = 'Schneems'").is_none());
+/// ```
+///
+/// ## Display names and releases
+///
+/// Follow a struct with `as "name"` to set its [`TryMigrate::version_name`], which is used
+/// in output and reports instead of the type name. Add `since "1.2.3"` (after `as` when
+/// both are used) to record the release that introduced it in [`TryMigrate::since`], and
+/// `help "https://..."` (last) to link to remediation docs in [`TryMigrate::help`]. This
+/// works for every `try_*` macro.
+///
+/// ```rust
+/// use magic_migrate::TryMigrate;
+#[doc = include_str!("fixtures/try_personV1_V2.txt")]
+///
+/// magic_migrate::try_migrate_deserializer_chain!(
+///     deserializer: toml::Deserializer::new,
+///     error: PersonMigrationError,
+///     chain: [
+///         PersonV1 as "Person v1" help "https://example.com/person-v1",
+///         PersonV2 as "Person v2" since "2.3.0"
+///     ],
+/// );
+///
+/// assert_eq!(PersonV1::version_name(), "Person v1");
+/// assert_eq!(PersonV2::version_name(), "Person v2");
+/// assert_eq!(PersonV1::since(), None);
+/// assert_eq!(PersonV2::since(), Some("2.3.0"));
+/// assert_eq!(PersonV2::help(), Some("https://example.com/person-v1"));
+///
+/// let error = PersonV2::try_from_str_migrations_result("name = 'Schneems'").unwrap_err();
+/// assert_eq!(error.help(), Some("https://example.com/person-v1"));
+/// assert_eq!(
+///     error.to_string(),
+///     "Could not migrate from `Person v1`: Title cannot be empty!!! (see https://example.com/person-v1)"
+/// );
+///
+/// let (version, _) =
+///     PersonV2::try_from_str_migrations_with_version("name = 'Schneems'\ntitle = 'Dev'").unwrap();
+/// assert_eq!(version, "Person v1");
+/// ```
+///
+/// ## Argument order
+///
+/// `error:`, `deserializer:`, and `chain:` can be given in any order, including an error
+/// that's a path or has generics.
+///
+/// ```rust
+/// use magic_migrate::TryMigrate;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize, Debug)]
+/// struct PersonV1 {
+///     name: String,
+/// }
+///
+/// #[derive(Deserialize, Debug)]
+/// struct PersonV2 {
+///     full_name: String,
+/// }
+///
+/// impl TryFrom<PersonV1> for PersonV2 {
+///     type Error = Box<dyn std::error::Error>;
+///
+///     fn try_from(value: PersonV1) -> Result<Self, Self::Error> {
+///         if value.name.is_empty() {
+///             return Err("Name cannot be empty".into());
+///         }
+///         Ok(PersonV2 { full_name: value.name })
+///     }
+/// }
+///
+/// magic_migrate::try_migrate_deserializer_chain!(
+///     chain: [PersonV1, PersonV2],
+///     deserializer: toml::Deserializer::new,
+///     error: Box<dyn std::error::Error>,
+/// );
+///
+/// let person = PersonV2::try_from_str_migrations("name = 'Schneems'").unwrap().unwrap();
+/// assert_eq!(person.full_name, "Schneems".to_string());
+///
+/// let error = PersonV2::try_from_str_migrations("name = ''").unwrap().unwrap_err();
+/// assert_eq!(error.to_string(), "Name cannot be empty");
+/// ```
 #[macro_export]
 macro_rules! try_migrate_deserializer_chain {
-    // Base case
-    (error: $err:ident, deserializer: $deser:path, chain: [$a:ident] $(,)?) => {
-        impl TryMigrate for $a {
+    // Base case, `$infallible` is `yes` when the error needs a `From<Infallible>` impl
+    (@chain $infallible:ident, $err:ty, $deser:path, [$a:ident $(as $a_name:literal)? $(since $a_since:literal)? $(help $a_help:literal)?]) => {
+        impl $crate::TryMigrate for $a {
             type TryFrom = Self;
             type Error = $err;
 
-            fn deserializer<'de>(input: &str) -> impl serde::de::Deserializer<'de> {
+            fn deserializer<'de>(input: &str) -> impl $crate::__private::serde::de::Deserializer<'de> {
                 $deser(input)
             }
+
+            $(
+                fn version_name() -> &'static str {
+                    $a_name
+                }
+            )?
+
+            $(
+                fn since() -> ::std::option::Option<&'static str> {
+                    ::std::option::Option::Some($a_since)
+                }
+            )?
+
+            $(
+                fn help() -> ::std::option::Option<&'static str> {
+                    ::std::option::Option::Some($a_help)
+                }
+            )?
         }
-        impl From<std::convert::Infallible> for $err {
-            fn from(_value: std::convert::Infallible) -> Self {
-                unreachable!();
-            }
-        }
+
+        $crate::__from_infallible!($infallible $err);
     };
+    // Feature gated first struct, the next struct starts the chain when it's compiled out
+    (@chain $infallible:ident, $err:ty, $deser:path, [#[cfg($pred:meta)] $a:ident $(as $a_name:literal)? $(since $a_since:literal)? $(help $a_help:literal)?, $($rest:tt)+]) => (
+        #[cfg($pred)]
+        $crate::try_migrate_deserializer_chain!(@chain $infallible, $err, $deser, [$a $(as $a_name)? $(since $a_since)? $(help $a_help)?, $($rest)+]);
+
+        #[cfg(not($pred))]
+        $crate::try_migrate_deserializer_chain!(@chain $infallible, $err, $deser, [$($rest)+]);
+    );
     // Rest case
-    (error: $err:ident, deserializer: $deser:path, chain: [$a:ident, $($rest:ident),+] $(,)?) => (
+    (@chain $infallible:ident, $err:ty, $deser:path, [$a:ident $(as $a_name:literal)? $(since $a_since:literal)? $(help $a_help:literal)?, $($rest:tt)+]) => (
         // Call the base case to link A => A
-        $crate::try_migrate_deserializer_chain!(error: $err, deserializer: $deser, chain: [$a]);
+        $crate::try_migrate_deserializer_chain!(@chain $infallible, $err, $deser, [$a $(as $a_name)? $(since $a_since)? $(help $a_help)?]);
 
         // Link the rest i.e. A => B, B => C, etc.
-        $crate::try_migrate_link!($a, $($rest),+);
+        $crate::try_migrate_link!($a, $($rest)+);
     );
 
-    // Base case variants
-    (error: $err:ident, chain: [$a:ident], deserializer: $deser:path $(,)?) => {
-        $crate::try_migrate_deserializer_chain!(error: $err, deserializer: $deser, chain: [$a]);
+    // An error enum defined in the calling crate
+    (error: $err:ident, deserializer: $deser:path, chain: [$($chain:tt)+] $(,)?) => {
+        $crate::try_migrate_deserializer_chain!(@chain yes, $err, $deser, [$($chain)+]);
+    };
+    // Any other error type such as `Box<dyn std::error::Error + Send + Sync>`, which
+    // already converts from `Infallible`
+    (error: $err:ty, deserializer: $deser:path, chain: [$($chain:tt)+] $(,)?) => {
+        $crate::try_migrate_deserializer_chain!(@chain no, $err, $deser, [$($chain)+]);
+    };
+
+    // Without an error every link must be infallible
+    (deserializer: $deser:path, chain: [$($chain:tt)+] $(,)?) => {
+        $crate::try_migrate_deserializer_chain!(
+            error: ::std::convert::Infallible,
+            deserializer: $deser,
+            chain: [$($chain)+]
+        );
+    };
+    (chain: [$($chain:tt)+], deserializer: $deser:path $(,)?) => {
+        $crate::try_migrate_deserializer_chain!(deserializer: $deser, chain: [$($chain)+]);
+    };
+
+    // Position variants, an error that's a type name is matched as `ident` first so it
+    // still gets a `From<Infallible>` impl
+    (error: $err:ident, chain: [$($chain:tt)+], deserializer: $deser:path $(,)?) => {
+        $crate::try_migrate_deserializer_chain!(error: $err, deserializer: $deser, chain: [$($chain)+]);
+    };
+    (error: $err:ty, chain: [$($chain:tt)+], deserializer: $deser:path $(,)?) => {
+        $crate::try_migrate_deserializer_chain!(error: $err, deserializer: $deser, chain: [$($chain)+]);
+    };
+    (chain: [$($chain:tt)+], deserializer: $deser:path, error: $err:ident $(,)?) => {
+        $crate::try_migrate_deserializer_chain!(error: $err, deserializer: $deser, chain: [$($chain)+]);
+    };
+    (chain: [$($chain:tt)+], deserializer: $deser:path, error: $err:ty $(,)?) => {
+        $crate::try_migrate_deserializer_chain!(error: $err, deserializer: $deser, chain: [$($chain)+]);
+    };
+    (chain: [$($chain:tt)+], error: $err:ident, deserializer: $deser:path $(,)?) => {
+        $crate::try_migrate_deserializer_chain!(error: $err, deserializer: $deser, chain: [$($chain)+]);
+    };
+    (chain: [$($chain:tt)+], error: $err:ty, deserializer: $deser:path $(,)?) => {
+        $crate::try_migrate_deserializer_chain!(error: $err, deserializer: $deser, chain: [$($chain)+]);
+    };
+    (deserializer: $deser:path, chain: [$($chain:tt)+], error: $err:ident $(,)?) => {
+        $crate::try_migrate_deserializer_chain!(error: $err, deserializer: $deser, chain: [$($chain)+]);
+    };
+    (deserializer: $deser:path, chain: [$($chain:tt)+], error: $err:ty $(,)?) => {
+        $crate::try_migrate_deserializer_chain!(error: $err, deserializer: $deser, chain: [$($chain)+]);
+    };
+    (deserializer: $deser:path, error: $err:ident, chain: [$($chain:tt)+] $(,)?) => {
+        $crate::try_migrate_deserializer_chain!(error: $err, deserializer: $deser, chain: [$($chain)+]);
+    };
+    (deserializer: $deser:path, error: $err:ty, chain: [$($chain:tt)+] $(,)?) => {
+        $crate::try_migrate_deserializer_chain!(error: $err, deserializer: $deser, chain: [$($chain)+]);
+    };
+}
+
+/// Extend a [`TryMigrate`] chain defined somewhere else, such as in a library crate, with
+/// more structs and a new error type
+///
+/// The first struct in `chain:` migrates from `from:` and every struct in `chain:` uses
+/// `error:`, which must implement `From` for the error of the chain being extended. The
+/// deserializer is inherited from `from:`.
+///
+/// ```rust
+/// use magic_migrate::TryMigrate;
+///
+/// // Stands in for a library crate
+/// mod shared {
+///     use serde::Deserialize;
+///
+///     #[derive(Deserialize, Debug)]
+///     #[serde(deny_unknown_fields)]
+///     pub struct MetadataV1 {
+///         pub version: String,
+///     }
+///
+///     #[derive(Deserialize, Debug)]
+///     #[serde(deny_unknown_fields)]
+///     pub struct MetadataV2 {
+///         pub version: u64,
+///     }
+///
+///     impl TryFrom<MetadataV1> for MetadataV2 {
+///         type Error = std::num::ParseIntError;
+///
+///         fn try_from(value: MetadataV1) -> Result<Self, Self::Error> {
+///             Ok(MetadataV2 { version: value.version.parse()? })
+///         }
+///     }
+///
+///     #[derive(Debug, thiserror::Error)]
+///     pub enum SharedError {
+///         #[error("Invalid version: {0}")]
+///         Version(#[from] std::num::ParseIntError),
+///     }
+///
+///     magic_migrate::try_migrate_toml_chain!(
+///         error: SharedError,
+///         chain: [MetadataV1, MetadataV2],
+///     );
+/// }
+///
+/// #[derive(serde::Deserialize, Debug)]
+/// #[serde(deny_unknown_fields)]
+/// struct MetadataV3 {
+///     version: u64,
+///     stack: String,
+/// }
+///
+/// impl TryFrom<shared::MetadataV2> for MetadataV3 {
+///     type Error = StackError;
+///
+///     fn try_from(value: shared::MetadataV2) -> Result<Self, Self::Error> {
+///         if value.version == 0 {
+///             Err(StackError)
+///         } else {
+///             Ok(MetadataV3 { version: value.version, stack: "heroku-24".to_string() })
+///         }
+///     }
+/// }
+///
+/// #[derive(Debug, thiserror::Error)]
+/// #[error("Version 0 has no stack")]
+/// struct StackError;
+///
+/// #[derive(Debug, thiserror::Error)]
+/// enum AppError {
+///     #[error(transparent)]
+///     Shared(#[from] shared::SharedError),
+///     #[error(transparent)]
+///     Stack(#[from] StackError),
+/// }
+///
+/// magic_migrate::try_migrate_extend_chain!(
+///     error: AppError,
+///     from: shared::MetadataV2,
+///     chain: [MetadataV3],
+/// );
+///
+/// let metadata = MetadataV3::try_from_str_migrations("version = '3'").unwrap().unwrap();
+/// assert_eq!(metadata.stack, "heroku-24".to_string());
+///
+/// let result = MetadataV3::try_from_str_migrations("version = 'three'").unwrap();
+/// assert!(matches!(result, Err(AppError::Shared(_))));
+/// ```
+#[macro_export]
+macro_rules! try_migrate_extend_chain {
+    (error: $err:ident, from: $from:path, chain: [$($chain:tt)+] $(,)?) => {
+        $crate::try_migrate_extend_chain!(@chain yes, $err, $from, [$($chain)+]);
+    };
+    (error: $err:ty, from: $from:path, chain: [$($chain:tt)+] $(,)?) => {
+        $crate::try_migrate_extend_chain!(@chain no, $err, $from, [$($chain)+]);
+    };
+    (@chain $infallible:ident, $err:ty, $from:path, [$a:ident $(as $a_name:literal)? $(since $a_since:literal)? $(help $a_help:literal)? $(, $($rest:tt)+)?]) => {
+        impl $crate::TryMigrate for $a {
+            type TryFrom = $from;
+            type Error = $err;
+
+            fn deserializer<'de>(input: &str) -> impl $crate::__private::serde::de::Deserializer<'de> {
+                <<Self as $crate::TryMigrate>::TryFrom as $crate::TryMigrate>::deserializer(input)
+            }
+
+            $(
+                fn version_name() -> &'static str {
+                    $a_name
+                }
+            )?
+
+            $(
+                fn since() -> ::std::option::Option<&'static str> {
+                    ::std::option::Option::Some($a_since)
+                }
+            )?
+
+            $(
+                fn help() -> ::std::option::Option<&'static str> {
+                    ::std::option::Option::Some($a_help)
+                }
+            )?
+        }
+
+        $crate::__from_infallible!($infallible $err);
+
+        $(
+            $crate::try_migrate_link!($a, $($rest)+);
+        )?
+    };
+}
+
+/// Implement `From` for every source error of a [`TryMigrate`] chain's error enum
+///
+/// Each entry is a single field tuple variant and the type it wraps. Add `Infallible` as
+/// the first entry when the chain is linked by hand with [`try_migrate_link!`], the
+/// `*_chain!` macros already implement `From<std::convert::Infallible>`.
+///
+/// ```rust
+/// use magic_migrate::TryMigrate;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize, Debug)]
+/// struct CountV1 {
+///     count: String,
+/// }
+///
+/// #[derive(Deserialize, Debug)]
+/// struct CountV2 {
+///     count: u64,
+/// }
+///
+/// impl TryFrom<CountV1> for CountV2 {
+///     type Error = std::num::ParseIntError;
+///
+///     fn try_from(value: CountV1) -> Result<Self, Self::Error> {
+///         Ok(CountV2 {
+///             count: value.count.parse()?,
+///         })
+///     }
+/// }
+///
+/// #[derive(Debug, thiserror::Error)]
+/// enum CountError {
+///     #[error("Invalid count: {0}")]
+///     NotANumber(std::num::ParseIntError),
+/// }
+///
+/// magic_migrate::try_migrate_error_from!(CountError {
+///     NotANumber(std::num::ParseIntError),
+/// });
+///
+/// magic_migrate::try_migrate_toml_chain!(
+///     error: CountError,
+///     chain: [CountV1, CountV2],
+/// );
+///
+/// let count = CountV2::try_from_str_migrations("count = '3'").unwrap().unwrap();
+/// assert_eq!(count.count, 3);
+///
+/// let result = CountV2::try_from_str_migrations("count = 'three'").unwrap();
+/// assert!(matches!(result, Err(CountError::NotANumber(_))));
+/// ```
+#[macro_export]
+macro_rules! try_migrate_error_from {
+    ($err:ident { Infallible $(, $variant:ident($source:ty))* $(,)? }) => {
+        impl ::std::convert::From<::std::convert::Infallible> for $err {
+            fn from(value: ::std::convert::Infallible) -> Self {
+                match value {}
+            }
+        }
+
+        $crate::try_migrate_error_from!($err { $($variant($source)),* });
     };
-    (chain: [$a:ident], deserializer: $deser:path, error: $err:ident $(,)?) => {
-        $crate::try_migrate_deserializer_chain!(error: $err, deserializer: $deser, chain: [$a]);
+    ($err:ident { $($variant:ident($source:ty)),* $(,)? }) => {
+        $(
+            impl ::std::convert::From<$source> for $err {
+                fn from(value: $source) -> Self {
+                    $err::$variant(value)
+                }
+            }
+        )*
     };
-    (chain: [$a:ident], error: $err:ident, deserializer: $deser:path $(,)?) => {
-        $crate::try_migrate_deserializer_chain!(error: $err, deserializer: $deser, chain: [$a]);
+}
+
+/// Implement `FromStr` for the latest struct of a [`TryMigrate`] chain
+///
+/// `str::parse` runs the input through [`TryMigrate::try_from_str_migrations_result`], the
+/// error is a [`MigrateError`] of the chain's error.
+///
+/// ```rust
+/// use magic_migrate::{MigrateError, TryMigrate};
+#[doc = include_str!("fixtures/try_personV1_V2.txt")]
+///
+/// magic_migrate::try_migrate_toml_chain!(
+///     error: PersonMigrationError,
+///     chain: [PersonV1, PersonV2],
+/// );
+///
+/// magic_migrate::try_migrate_from_str!(PersonV2);
+///
+/// let person: PersonV2 = "name = 'Schneems'\ntitle = 'Dev'".parse().unwrap();
+/// assert_eq!(person.job_title, "Dev".to_string());
+///
+/// let result = "".parse::<PersonV2>();
+/// assert!(matches!(result, Err(MigrateError::NoMatchingVersion)));
+/// ```
+#[macro_export]
+macro_rules! try_migrate_from_str {
+    ($latest:ident) => {
+        impl ::std::str::FromStr for $latest {
+            type Err = $crate::MigrateError<<$latest as $crate::TryMigrate>::Error>;
+
+            fn from_str(input: &str) -> ::std::result::Result<Self, Self::Err> {
+                <$latest as $crate::TryMigrate>::try_from_str_migrations_result(input)
+            }
+        }
     };
-    (deserializer: $deser:path, chain: [$a:ident], error: $err:ident $(,)?) => {
-        $crate::try_migrate_deserializer_chain!(error: $err, deserializer: $deser, chain: [$a]);
+}
+
+/// Fail compilation unless the structs form a [`TryMigrate`] chain in the given order
+///
+/// The first struct must be the root of the chain (it migrates from itself) and each
+/// following struct must migrate from the one before it. Use it to catch a chain that was
+/// forked or reordered by accident, for example when versions are spread across files.
+///
+/// ```rust
+/// use magic_migrate::TryMigrate;
+#[doc = include_str!("fixtures/try_personV1_V2.txt")]
+///
+/// magic_migrate::try_migrate_toml_chain!(
+///     error: PersonMigrationError,
+///     chain: [PersonV1, PersonV2],
+/// );
+///
+/// magic_migrate::audit_chain!(PersonV1 => PersonV2);
+/// magic_migrate::audit_chain!(PersonV1 => PersonV2, max_len: 2);
+/// ```
+///
+/// Out of order structs do not compile:
+///
+/// ```compile_fail
+/// use magic_migrate::TryMigrate;
+#[doc = include_str!("fixtures/try_personV1_V2.txt")]
+///
+/// magic_migrate::try_migrate_toml_chain!(
+///     error: PersonMigrationError,
+///     chain: [PersonV1, PersonV2],
+/// );
+///
+/// magic_migrate::audit_chain!(PersonV2 => PersonV1);
+/// ```
+///
+/// Add `max_len:` to also fail compilation when more than that many structs are listed, a
+/// nudge to squash old versions deliberately. Only the listed structs are counted, a trait
+/// impl can't tell whether a newer struct migrates from the last one. List the whole chain,
+/// down to its latest struct, for `max_len:` to bound the chain's length:
+///
+/// ```compile_fail
+/// use magic_migrate::TryMigrate;
+#[doc = include_str!("fixtures/try_personV1_V2.txt")]
+///
+/// magic_migrate::try_migrate_toml_chain!(
+///     error: PersonMigrationError,
+///     chain: [PersonV1, PersonV2],
+/// );
+///
+/// magic_migrate::audit_chain!(PersonV1 => PersonV2, max_len: 1);
+/// ```
+#[macro_export]
+macro_rules! audit_chain {
+    (@links $a:ty => $b:ty $(=> $rest:ty)*) => {
+        let _: ::core::marker::PhantomData<<$b as $crate::TryMigrate>::TryFrom> =
+            ::core::marker::PhantomData::<$a>;
+
+        $crate::audit_chain!(@links $b $(=> $rest)*);
     };
-    (deserializer: $deser:path, error: $err:ident, chain: [$a:ident], $(,)?) => {
-        $crate::try_migrate_deserializer_chain!(error: $err, deserializer: $deser, chain: [$a]);
+    (@links $a:ty) => {};
+    ($root:ty $(=> $link:ty)*, max_len: $max:expr $(,)?) => {
+        $crate::audit_chain!($root $(=> $link)*);
+
+        const _: () = ::core::assert!(
+            [::core::stringify!($root) $(, ::core::stringify!($link))*].len() <= $max,
+            "The audited chain lists more structs than `max_len`"
+        );
     };
-    // Rest case variants
-    (error: $err:ident, chain: [$a:ident, $($rest:ident),+] , deserializer: $deser:path $(,)?) => {
-        $crate::try_migrate_deserializer_chain!(error: $err, deserializer: $deser, chain: [$a, $($rest),+]);
+    ($root:ty $(=> $link:ty)* $(,)?) => {
+        const _: fn() = || {
+            // The root migrates from itself
+            let _: ::core::marker::PhantomData<<$root as $crate::TryMigrate>::TryFrom> =
+                ::core::marker::PhantomData::<$root>;
+
+            $crate::audit_chain!(@links $root $(=> $link)*);
+        };
     };
-    (chain: [$a:ident, $($rest:ident),+], deserializer: $deser:path, error: $err:ident $(,)?) => {
-        $crate::try_migrate_deserializer_chain!(error: $err, deserializer: $deser, chain: [$a, $($rest),+]);
+}
+
+/// A [`TryMigrate`] chain whose first struct is read by a hand written parser instead of serde
+///
+/// Use it when the oldest format predates serde. The parser is a `fn(&str) -> Result<V0, E>`
+/// where `E: Display`. The other structs in the chain use `deserializer:` as usual and the
+/// first of them must implement `TryFrom<V0>` (or `From<V0>`).
+///
+/// The `root:` struct gets a [`serde::Deserialize`] impl that calls the parser on a string,
+/// it must not implement `Deserialize` itself.
+///
+/// ```rust
+/// use magic_migrate::TryMigrate;
+/// use serde::Deserialize;
+///
+/// #[derive(Debug)]
+/// struct CacheV0 {
+///     ruby: String,
+/// }
+///
+/// // Hand rolled `key=value` lines
+/// fn parse_v0(input: &str) -> Result<CacheV0, String> {
+///     input
+///         .lines()
+///         .find_map(|line| line.strip_prefix("ruby="))
+///         .map(|ruby| CacheV0 { ruby: ruby.trim().to_string() })
+///         .ok_or_else(|| "Missing `ruby=`".to_string())
+/// }
+///
+/// #[derive(Deserialize, Debug)]
+/// struct CacheV1 {
+///     ruby_version: String,
+/// }
+///
+/// impl From<CacheV0> for CacheV1 {
+///     fn from(value: CacheV0) -> Self {
+///         CacheV1 { ruby_version: value.ruby }
+///     }
+/// }
+///
+/// #[derive(Debug, thiserror::Error)]
+/// #[error("Unreachable")]
+/// struct CacheError;
+///
+/// magic_migrate::try_migrate_parser_chain!(
+///     error: CacheError,
+///     parser: parse_v0,
+///     root: CacheV0,
+///     deserializer: toml::Deserializer::new,
+///     chain: [CacheV1],
+/// );
+///
+/// let cache = CacheV1::try_from_str_migrations("ruby=3.1.0").unwrap().unwrap();
+/// assert_eq!(cache.ruby_version, "3.1.0".to_string());
+///
+/// let cache = CacheV1::try_from_str_migrations("ruby_version = '3.3.0'").unwrap().unwrap();
+/// assert_eq!(cache.ruby_version, "3.3.0".to_string());
+///
+/// assert!(CacheV1::try_from_str_migrations("python=3.12").is_none());
+/// assert!(CacheV1::check_deserializers().is_ok());
+/// ```
+#[macro_export]
+macro_rules! try_migrate_parser_chain {
+    (
+        error: $err:ident,
+        parser: $parser:path,
+        root: $root:ident,
+        deserializer: $deser:path,
+        chain: [$a:ident $(as $a_name:literal)? $(since $a_since:literal)? $(help $a_help:literal)? $(, $($rest:tt)+)?] $(,)?
+    ) => {
+        impl<'de> $crate::__private::serde::Deserialize<'de> for $root {
+            fn deserialize<D: $crate::__private::serde::de::Deserializer<'de>>(deserializer: D) -> ::std::result::Result<Self, D::Error> {
+                let input = <::std::string::String as $crate::__private::serde::Deserialize>::deserialize(deserializer)?;
+                $parser(&input).map_err($crate::__private::serde::de::Error::custom)
+            }
+        }
+
+        impl $crate::TryMigrate for $root {
+            type TryFrom = Self;
+            type Error = $err;
+
+            fn deserializer<'de>(input: &str) -> impl $crate::__private::serde::de::Deserializer<'de> {
+                $crate::__private::serde::de::IntoDeserializer::<$crate::__private::serde::de::value::Error>::into_deserializer(input)
+            }
+        }
+
+        impl ::std::convert::From<::std::convert::Infallible> for $err {
+            fn from(value: ::std::convert::Infallible) -> Self {
+                match value {}
+            }
+        }
+
+        impl $crate::TryMigrate for $a {
+            type TryFrom = $root;
+            type Error = <<Self as $crate::TryMigrate>::TryFrom as $crate::TryMigrate>::Error;
+
+            const CHANGES_FORMAT: bool = true;
+
+            fn deserializer<'de>(input: &str) -> impl $crate::__private::serde::de::Deserializer<'de> {
+                $deser(input)
+            }
+
+            $(
+                fn version_name() -> &'static str {
+                    $a_name
+                }
+            )?
+
+            $(
+                fn since() -> ::std::option::Option<&'static str> {
+                    ::std::option::Option::Some($a_since)
+                }
+            )?
+
+            $(
+                fn help() -> ::std::option::Option<&'static str> {
+                    ::std::option::Option::Some($a_help)
+                }
+            )?
+        }
+
+        $(
+            $crate::try_migrate_link!($a, $($rest)+);
+        )?
     };
-    (chain: [$a:ident, $($rest:ident),+], error: $err:ident, deserializer: $deser:path $(,)?) => {
-        $crate::try_migrate_deserializer_chain!(error: $err, deserializer: $deser, chain: [$a, $($rest),+]);
+}
+
+/// Build [`Jumps`] to the latest struct from each of the listed older structs
+///
+/// `try_migrate_jumps!([PersonV1] => PersonV3)` is the same as
+/// `Jumps::<PersonV3>::new().jump::<PersonV1>()`, see [`Jumps`] for an example.
+#[macro_export]
+macro_rules! try_migrate_jumps {
+    ([$($from:ty),* $(,)?] => $to:ty) => {
+        $crate::Jumps::<$to>::new()$(.jump::<$from>())*
     };
-    (deserializer: $deser:path, chain: [$a:ident, $($rest:ident),+], error: $err:ident $(,)?) => {
-        $crate::try_migrate_deserializer_chain!(error: $err, deserializer: $deser, chain: [$a, $($rest),+]);
+}
+
+/// Used by the macros so crates that define chains don't need their own `serde` or `toml`
+/// dependency
+#[doc(hidden)]
+pub mod __private {
+    pub use serde;
+    #[cfg(feature = "toml")]
+    pub use toml;
+}
+
+/// Calls a `*_deserializer_chain!` macro with the `toml` deserializer between the `before`
+/// and `after` arguments, from the re-export
+/// when the `toml` feature is enabled and from the calling crate's own `toml` otherwise
+#[cfg(feature = "toml")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __with_toml_deserializer {
+    ($chain_macro:ident { $($before:tt)* } { $($after:tt)+ }) => {
+        $crate::$chain_macro!($($before)* deserializer: $crate::__private::toml::Deserializer::new, $($after)+);
     };
-    (deserializer: $deser:path, error: $err:ident, chain: [$a:ident, $($rest:ident),+] $(,)?) => {
-        $crate::try_migrate_deserializer_chain!(error: $err, deserializer: $deser, chain: [$a, $($rest),+]);
+}
+
+#[cfg(not(feature = "toml"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __with_toml_deserializer {
+    ($chain_macro:ident { $($before:tt)* } { $($after:tt)+ }) => {
+        $crate::$chain_macro!($($before)* deserializer: toml::Deserializer::new, $($after)+);
+    };
+}
+
+/// Implements `From<Infallible>` for a chain's error when the first argument is `yes`
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __from_infallible {
+    (yes $err:ty) => {
+        impl ::std::convert::From<::std::convert::Infallible> for $err {
+            fn from(value: ::std::convert::Infallible) -> Self {
+                match value {}
+            }
+        }
     };
+    (no $err:ty) => {};
 }
@@ -0,0 +1,154 @@
+use std::fmt::Display;
+
+/// Bounds on input that are checked before any struct in the chain is deserialized
+///
+/// Used by [`crate::TryMigrate::try_from_str_migrations_limited`] to reject untrusted
+/// input that is too large or so deeply nested that parsing it could overflow the stack.
+///
+/// Nesting is measured by counting brackets (`[` and `{`) without parsing the format.
+/// Brackets inside of JSON and TOML strings (`"`, `'`, and their triple quoted forms) and
+/// `#` comments are skipped. The nesting of XML elements and of YAML's indented blocks is
+/// not measured, and for YAML a `'` in an unquoted value throws the count off.
+///
+/// ```rust
+/// use magic_migrate::Limits;
+///
+/// let limits = Limits::new().max_bytes(1024).max_depth(2);
+/// assert!(limits.check("a = [[1]]").is_ok());
+/// assert!(limits.check("a = [[[1]]]").is_err());
+/// assert!(limits.check(&"a".repeat(1025)).is_err());
+///
+/// // Brackets in strings and comments don't count
+/// assert!(limits.check("a = [[']]]', \"[[[\\\"\"]] # [[[").is_ok());
+/// assert!(limits.check(r#"[ "]", [ "]", [ "]", [ "]" ] ] ] ]"#).is_err());
+/// assert!(limits.check(r#"a = """]]"""" b = [[1]]"#).is_ok());
+/// assert!(limits.check(r#"a = """]]"""" b = [[[1]]]"#).is_err());
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Limits {
+    max_bytes: Option<usize>,
+    max_depth: Option<usize>,
+}
+
+impl Limits {
+    /// No limits, add them with [`Limits::max_bytes`] and [`Limits::max_depth`]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reject input longer than `bytes`
+    #[must_use]
+    pub fn max_bytes(mut self, bytes: usize) -> Self {
+        self.max_bytes = Some(bytes);
+        self
+    }
+
+    /// Reject input with more than `depth` levels of nested arrays or tables
+    #[must_use]
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// Returns an error when `input` is outside of the limits
+    pub fn check(&self, input: &str) -> Result<(), LimitExceeded> {
+        if let Some(limit) = self.max_bytes {
+            if input.len() > limit {
+                return Err(LimitExceeded::Bytes {
+                    limit,
+                    actual: input.len(),
+                });
+            }
+        }
+
+        if let Some(limit) = self.max_depth {
+            if nests_deeper(input.as_bytes(), limit) {
+                return Err(LimitExceeded::Depth { limit });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// True when brackets outside of strings and comments nest deeper than `limit`
+fn nests_deeper(bytes: &[u8], limit: usize) -> bool {
+    let mut depth = 0_usize;
+    let mut index = 0;
+    while let Some(byte) = bytes.get(index) {
+        index += 1;
+        match byte {
+            b'[' | b'{' => {
+                depth += 1;
+                if depth > limit {
+                    return true;
+                }
+            }
+            b']' | b'}' => depth = depth.saturating_sub(1),
+            b'#' => {
+                index = bytes[index..]
+                    .iter()
+                    .position(|byte| *byte == b'\n')
+                    .map_or(bytes.len(), |end| index + end);
+            }
+            b'"' | b'\'' => {
+                let escapes = *byte == b'"';
+                let triple = [*byte; 3];
+                index = if bytes[index - 1..].starts_with(&triple) {
+                    let end = skip_string(bytes, index + 2, &triple, escapes);
+                    // TOML allows up to two quotes right before the closing delimiter
+                    end + bytes[end..]
+                        .iter()
+                        .take(2)
+                        .take_while(|next| *next == byte)
+                        .count()
+                } else {
+                    skip_string(bytes, index, &triple[..1], escapes)
+                };
+            }
+            _ => {}
+        }
+    }
+    false
+}
+
+/// The index after the `end` delimiter of a string whose contents start at `index`, a `\`
+/// escapes the byte after it when `escapes` is true
+fn skip_string(bytes: &[u8], mut index: usize, end: &[u8], escapes: bool) -> usize {
+    while index < bytes.len() {
+        if escapes && bytes[index] == b'\\' {
+            index += 2;
+        } else if bytes[index..].starts_with(end) {
+            return index + end.len();
+        } else {
+            index += 1;
+        }
+    }
+    bytes.len()
+}
+
+/// Input was rejected by [`Limits`] before it was deserialized
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LimitExceeded {
+    /// The input is longer than the limit
+    Bytes { limit: usize, actual: usize },
+    /// The input nests deeper than the limit
+    Depth { limit: usize },
+}
+
+impl Display for LimitExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LimitExceeded::Bytes { limit, actual } => {
+                write!(f, "Input is {actual} bytes, the limit is {limit}")
+            }
+            LimitExceeded::Depth { limit } => {
+                write!(f, "Input nests deeper than the limit of {limit}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for LimitExceeded {}
@@ -0,0 +1,196 @@
+//! Remember migration results for inputs that are seen over and over
+
+use crate::schema::{fnv1a, FNV_OFFSET};
+use crate::{walk, TryMigrate};
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Debug;
+
+/// Remembers the result of migrating recently seen inputs through the chain of `T`
+///
+/// For servers that see the same payloads over and over, such as retried webhooks. Results
+/// are keyed by a hash of the input, the input is stored too so two inputs with the same
+/// hash never share a result. Failures and inputs that match no struct are remembered as
+/// well. Once `capacity` inputs are remembered, the least recently used one is dropped.
+///
+/// ```rust
+/// use magic_migrate::{Memoized, TryMigrate};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize, Debug, Clone)]
+/// #[serde(deny_unknown_fields)]
+/// struct PersonV1 {
+///     name: String,
+/// }
+///
+/// #[derive(Deserialize, Debug, Clone)]
+/// #[serde(deny_unknown_fields)]
+/// struct PersonV2 {
+///     full_name: String,
+/// }
+///
+/// impl From<PersonV1> for PersonV2 {
+///     fn from(value: PersonV1) -> Self {
+///         PersonV2 { full_name: value.name }
+///     }
+/// }
+///
+/// magic_migrate::try_migrate_toml_chain!(chain: [PersonV1, PersonV2]);
+///
+/// let mut memoized = Memoized::<PersonV2>::new(2);
+/// let person = memoized.try_from_str_migrations("name = 'Schneems'").unwrap().unwrap();
+/// assert_eq!(person.full_name, "Schneems".to_string());
+///
+/// memoized.try_from_str_migrations("name = 'Schneems'");
+/// memoized.try_from_str_migrations("name = 'Richard'");
+/// memoized.try_from_str_migrations("name = 'Terence'");
+/// assert_eq!(memoized.len(), 2);
+/// assert_eq!((memoized.hits(), memoized.misses()), (1, 3));
+///
+/// // `Schneems` was used least recently, so it was dropped
+/// memoized.try_from_str_migrations("name = 'Richard'");
+/// memoized.try_from_str_migrations("name = 'Schneems'");
+/// assert_eq!((memoized.hits(), memoized.misses()), (2, 4));
+/// ```
+pub struct Memoized<T: TryMigrate> {
+    capacity: usize,
+    entries: HashMap<u64, Entry<T>>,
+    /// Keys in the order they were used, oldest first. A key is pushed again each time
+    /// it's used, only the pair whose tick matches the entry's `used` is current.
+    recency: VecDeque<(u64, u64)>,
+    /// Incremented on every lookup, an entry's `used` is the tick it was last returned
+    tick: u64,
+    hits: u64,
+    misses: u64,
+}
+
+struct Entry<T: TryMigrate> {
+    input: String,
+    result: Option<Result<T, <T as TryMigrate>::Error>>,
+    used: u64,
+}
+
+impl<T> Memoized<T>
+where
+    T: TryMigrate + Clone,
+    <T as TryMigrate>::Error: Clone,
+{
+    /// Remember the results of up to `capacity` inputs, a capacity of 0 remembers nothing
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: HashMap::new(),
+            recency: VecDeque::new(),
+            tick: 0,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Same as [`TryMigrate::try_from_str_migrations`] but returns a clone of the remembered
+    /// result when `input` was seen before
+    pub fn try_from_str_migrations(
+        &mut self,
+        input: &str,
+    ) -> Option<Result<T, <T as TryMigrate>::Error>> {
+        self.tick += 1;
+        let key = fnv1a(FNV_OFFSET, input.as_bytes());
+        if let Some(entry) = self
+            .entries
+            .get_mut(&key)
+            .filter(|entry| entry.input == input)
+        {
+            entry.used = self.tick;
+            self.hits += 1;
+            let result = entry.result.clone();
+            self.touch(key);
+            return result;
+        }
+
+        self.misses += 1;
+        let result = walk::walk::<T>(input).map(|(_, result)| result);
+        if self.capacity > 0 {
+            if self.entries.len() >= self.capacity && !self.entries.contains_key(&key) {
+                self.evict();
+            }
+            self.entries.insert(
+                key,
+                Entry {
+                    input: input.to_string(),
+                    result: result.clone(),
+                    used: self.tick,
+                },
+            );
+            self.touch(key);
+        }
+        result
+    }
+
+    /// Record that `key` was used at the current tick
+    fn touch(&mut self, key: u64) {
+        self.recency.push_back((key, self.tick));
+        // Drop pairs that are no longer current so hits don't grow the queue forever
+        if self.recency.len() > 2 * self.capacity {
+            let entries = &self.entries;
+            self.recency
+                .retain(|(key, used)| entries.get(key).is_some_and(|entry| entry.used == *used));
+        }
+    }
+
+    /// Drop the least recently used entry
+    fn evict(&mut self) {
+        while let Some((key, used)) = self.recency.pop_front() {
+            if self
+                .entries
+                .get(&key)
+                .is_some_and(|entry| entry.used == used)
+            {
+                self.entries.remove(&key);
+                return;
+            }
+        }
+    }
+}
+
+impl<T: TryMigrate> Memoized<T> {
+    /// How many inputs are remembered
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// How many lookups returned a remembered result
+    #[must_use]
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// How many lookups ran the input through the chain
+    #[must_use]
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    /// Forget every remembered result
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.recency.clear();
+    }
+}
+
+impl<T: TryMigrate> Debug for Memoized<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Memoized")
+            .field("latest", &T::version_name())
+            .field("capacity", &self.capacity)
+            .field("len", &self.entries.len())
+            .field("hits", &self.hits)
+            .field("misses", &self.misses)
+            .finish()
+    }
+}
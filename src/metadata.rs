@@ -0,0 +1,97 @@
+//! A serializable description of a migration chain for external tooling
+
+use crate::TryMigrate;
+use serde::ser::SerializeStruct;
+use std::any::TypeId;
+
+/// Every struct in a chain, returned by [`crate::TryMigrate::chain_metadata`]
+///
+/// Implements [`serde::Serialize`] so dashboards, release checklists, and other tools can
+/// read the chain without parsing Rust source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ChainMetadata {
+    /// Each struct in the chain, oldest first
+    pub versions: Vec<VersionMetadata>,
+}
+
+/// One struct in a [`ChainMetadata`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct VersionMetadata {
+    /// The [`crate::TryMigrate::version_name`]
+    pub name: &'static str,
+    /// The full path of the type
+    pub type_name: &'static str,
+    /// The [`crate::TryMigrate::since`] release
+    pub since: Option<&'static str>,
+    /// The [`crate::TryMigrate::help`] link
+    pub help: Option<&'static str>,
+    /// The type name of the chain error this struct's conversion returns
+    pub error: &'static str,
+    /// The full path of the deserializer type, without generic parameters
+    pub deserializer: &'static str,
+    /// The [`crate::TryMigrate::schema`] as text
+    pub schema: String,
+    /// The [`crate::TryMigrate::schema_fingerprint`], serialized as 16 hex digits
+    pub schema_fingerprint: u64,
+    /// The [`crate::TryMigrate::chain_fingerprint`], serialized as 16 hex digits
+    pub chain_fingerprint: u64,
+}
+
+impl ChainMetadata {
+    pub(crate) fn of<T: TryMigrate>() -> Self {
+        let mut versions = Vec::new();
+        push::<T>(&mut versions);
+        Self { versions }
+    }
+}
+
+/// Pushes every struct before `T` then `T`
+fn push<T: TryMigrate>(versions: &mut Vec<VersionMetadata>) {
+    if TypeId::of::<T>() != TypeId::of::<T::TryFrom>() {
+        push::<T::TryFrom>(versions);
+    }
+    versions.push(VersionMetadata {
+        name: T::version_name(),
+        type_name: std::any::type_name::<T>(),
+        since: T::since(),
+        help: T::help(),
+        error: crate::short_type_name::<<T as TryMigrate>::Error>(),
+        deserializer: crate::docs::deserializer_name::<T>(),
+        schema: T::schema().to_string(),
+        schema_fingerprint: T::schema_fingerprint(),
+        chain_fingerprint: T::chain_fingerprint(),
+    });
+}
+
+impl serde::Serialize for ChainMetadata {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("ChainMetadata", 1)?;
+        state.serialize_field("versions", &self.versions)?;
+        state.end()
+    }
+}
+
+/// Fingerprints are written as hex strings, a `u64` doesn't fit in a JavaScript number
+impl serde::Serialize for VersionMetadata {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("VersionMetadata", 9)?;
+        state.serialize_field("name", self.name)?;
+        state.serialize_field("type_name", self.type_name)?;
+        state.serialize_field("since", &self.since)?;
+        state.serialize_field("help", &self.help)?;
+        state.serialize_field("error", self.error)?;
+        state.serialize_field("deserializer", self.deserializer)?;
+        state.serialize_field("schema", &self.schema)?;
+        state.serialize_field(
+            "schema_fingerprint",
+            &format!("{:016x}", self.schema_fingerprint),
+        )?;
+        state.serialize_field(
+            "chain_fingerprint",
+            &format!("{:016x}", self.chain_fingerprint),
+        )?;
+        state.end()
+    }
+}
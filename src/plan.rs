@@ -0,0 +1,30 @@
+use std::fmt::Display;
+
+/// What running input through a chain would do, returned by [`crate::TryMigrate::plan`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Plan {
+    /// The [`crate::TryMigrate::version_name`] of the struct that the input deserializes into
+    pub matched: &'static str,
+    /// The [`crate::TryMigrate::version_name`] of each struct it would be converted into, in
+    /// order, empty when the input is already the latest version
+    pub steps: Vec<&'static str>,
+}
+
+impl Plan {
+    /// True when no conversions would run
+    #[must_use]
+    pub fn is_latest(&self) -> bool {
+        self.steps.is_empty()
+    }
+}
+
+impl Display for Plan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "`{}`", self.matched)?;
+        for step in &self.steps {
+            write!(f, " => `{step}`")?;
+        }
+        Ok(())
+    }
+}
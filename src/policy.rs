@@ -0,0 +1,28 @@
+/// What to do when a struct in the chain deserializes but migrating it forward fails
+///
+/// Used by [`crate::TryMigrate::try_from_str_migrations_with_policy`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RecoveryPolicy {
+    /// Return the error from the failed conversion step (the default)
+    #[default]
+    FailFast,
+    /// Keep looking for an even older struct that deserializes and migrates cleanly. When
+    /// none does, the error from the newest struct that matched is returned.
+    ContinueWithOlder,
+}
+
+/// What to do with input that is empty or only whitespace
+///
+/// Used by [`crate::TryMigrate::try_from_str_migrations_with_empty`]. A struct where every
+/// field is optional or has a default deserializes from empty input in most formats, so
+/// empty input can match a version that it was never written as.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum EmptyInputPolicy {
+    /// Run empty input through the chain like any other input (the default)
+    #[default]
+    Deserialize,
+    /// Return [`crate::MigrateError::EmptyInput`] without trying any struct
+    Reject,
+}
@@ -0,0 +1,38 @@
+use serde::ser::SerializeStruct;
+use std::time::SystemTime;
+
+/// Where a migrated value came from, returned alongside it by
+/// [`crate::TryMigrate::try_from_str_migrations_with_provenance`]
+///
+/// Implements [`serde::Serialize`] so it can be stored next to the value for auditing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Provenance {
+    /// The [`crate::TryMigrate::version_name`] of the struct that the input was deserialized into
+    pub from: &'static str,
+    /// The [`crate::TryMigrate::since`] release of the struct that the input was deserialized into
+    pub from_since: Option<&'static str>,
+    /// The [`crate::TryMigrate::version_name`] of the struct it was migrated to
+    pub to: &'static str,
+    /// When the migration happened
+    pub migrated_at: SystemTime,
+}
+
+impl Provenance {
+    /// True when the input was an older version
+    #[must_use]
+    pub fn was_migrated(&self) -> bool {
+        self.from != self.to
+    }
+}
+
+impl serde::Serialize for Provenance {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Provenance", 4)?;
+        state.serialize_field("from", self.from)?;
+        state.serialize_field("from_since", &self.from_since)?;
+        state.serialize_field("to", self.to)?;
+        state.serialize_field("migrated_at", &self.migrated_at)?;
+        state.end()
+    }
+}
@@ -0,0 +1,330 @@
+//! Rename top-level keys of the input before any struct in a chain sees them
+//!
+//! [`RenameKeys`] wraps another deserializer. When the outermost value is a map (a struct,
+//! a TOML document, a JSON object) each key is looked up in the renames and handed to the
+//! visitor under its new name. Nothing is parsed into an intermediate value, so it works
+//! with any format.
+
+use serde::de::{self, DeserializeSeed, MapAccess, Visitor};
+
+/// A deserializer that renames the top-level keys of `D`
+///
+/// Use it when data moved to a different key, such as from `[metadata]` to `[cache]`, in a
+/// way that no single struct in the chain can express. Return it from the function passed
+/// as `deserializer:` to a chain macro or from [`crate::TryMigrate::deserializer`], every
+/// struct in the chain then sees the renamed keys. Nested keys and values are left alone.
+///
+/// ```rust
+/// use magic_migrate::{RenameKeys, TryMigrate};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize, Debug)]
+/// #[serde(deny_unknown_fields)]
+/// struct Cache {
+///     digest: String,
+/// }
+///
+/// #[derive(Deserialize, Debug)]
+/// #[serde(deny_unknown_fields)]
+/// struct LayerV1 {
+///     cache: Cache,
+/// }
+///
+/// #[derive(Deserialize, Debug)]
+/// #[serde(deny_unknown_fields)]
+/// struct LayerV2 {
+///     cache: Cache,
+///     version: u32,
+/// }
+///
+/// impl From<LayerV1> for LayerV2 {
+///     fn from(value: LayerV1) -> Self {
+///         LayerV2 { cache: value.cache, version: 1 }
+///     }
+/// }
+///
+/// // Old data is under `[metadata]`
+/// fn deserializer(input: &str) -> RenameKeys<toml::Deserializer<'_>> {
+///     RenameKeys::new(toml::Deserializer::new(input), &[("metadata", "cache")])
+/// }
+///
+/// magic_migrate::try_migrate_deserializer_chain!(
+///     deserializer: deserializer,
+///     chain: [LayerV1, LayerV2],
+/// );
+///
+/// let layer = LayerV2::try_from_str_migrations("[metadata]\ndigest = 'abc'")
+///     .unwrap()
+///     .unwrap();
+/// assert_eq!(layer.cache.digest, "abc".to_string());
+///
+/// let layer = LayerV2::try_from_str_migrations("version = 2\n[cache]\ndigest = 'def'")
+///     .unwrap()
+///     .unwrap();
+/// assert_eq!(layer.cache.digest, "def".to_string());
+/// assert_eq!(layer.version, 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct RenameKeys<D> {
+    inner: D,
+    renames: &'static [(&'static str, &'static str)],
+    /// True when `inner` deserializes a key of the top-level map
+    key: bool,
+}
+
+impl<D> RenameKeys<D> {
+    /// Wrap `inner`, each top-level key that matches the first name of a pair in `renames`
+    /// is renamed to the second
+    pub fn new(inner: D, renames: &'static [(&'static str, &'static str)]) -> Self {
+        Self {
+            inner,
+            renames,
+            key: false,
+        }
+    }
+
+    fn visitor<V>(&self, inner: V) -> Renaming<V> {
+        Renaming {
+            inner,
+            renames: self.renames,
+            key: self.key,
+        }
+    }
+}
+
+/// The new name of `key`, if it is renamed
+fn renamed(renames: &[(&'static str, &'static str)], key: &[u8]) -> Option<&'static str> {
+    renames
+        .iter()
+        .find(|(from, _)| from.as_bytes() == key)
+        .map(|(_, to)| *to)
+}
+
+macro_rules! forward_deserialize {
+    ($($method:ident($($arg:ident: $ty:ty),*);)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(
+                self,
+                $($arg: $ty,)*
+                visitor: V,
+            ) -> Result<V::Value, Self::Error> {
+                let visitor = self.visitor(visitor);
+                self.inner.$method($($arg,)* visitor)
+            }
+        )*
+    };
+}
+
+impl<'de, D: de::Deserializer<'de>> de::Deserializer<'de> for RenameKeys<D> {
+    type Error = D::Error;
+
+    forward_deserialize! {
+        deserialize_any();
+        deserialize_bool();
+        deserialize_i8();
+        deserialize_i16();
+        deserialize_i32();
+        deserialize_i64();
+        deserialize_i128();
+        deserialize_u8();
+        deserialize_u16();
+        deserialize_u32();
+        deserialize_u64();
+        deserialize_u128();
+        deserialize_f32();
+        deserialize_f64();
+        deserialize_char();
+        deserialize_str();
+        deserialize_string();
+        deserialize_bytes();
+        deserialize_byte_buf();
+        deserialize_option();
+        deserialize_unit();
+        deserialize_unit_struct(name: &'static str);
+        deserialize_newtype_struct(name: &'static str);
+        deserialize_seq();
+        deserialize_tuple(len: usize);
+        deserialize_tuple_struct(name: &'static str, len: usize);
+        deserialize_map();
+        deserialize_struct(name: &'static str, fields: &'static [&'static str]);
+        deserialize_enum(name: &'static str, variants: &'static [&'static str]);
+        deserialize_identifier();
+        deserialize_ignored_any();
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.inner.is_human_readable()
+    }
+}
+
+/// Wraps the visitor of the top-level value to rename the keys of a map, or the visitor of
+/// one of those keys to rename it
+struct Renaming<V> {
+    inner: V,
+    renames: &'static [(&'static str, &'static str)],
+    key: bool,
+}
+
+macro_rules! forward_visit {
+    ($($method:ident($ty:ty);)*) => {
+        $(
+            fn $method<E: de::Error>(self, v: $ty) -> Result<V::Value, E> {
+                self.inner.$method(v)
+            }
+        )*
+    };
+}
+
+impl<'de, V: Visitor<'de>> Visitor<'de> for Renaming<V> {
+    type Value = V::Value;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.inner.expecting(f)
+    }
+
+    forward_visit! {
+        visit_bool(bool);
+        visit_i8(i8);
+        visit_i16(i16);
+        visit_i32(i32);
+        visit_i64(i64);
+        visit_i128(i128);
+        visit_u8(u8);
+        visit_u16(u16);
+        visit_u32(u32);
+        visit_u64(u64);
+        visit_u128(u128);
+        visit_f32(f32);
+        visit_f64(f64);
+        visit_char(char);
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<V::Value, E> {
+        match renamed(self.renames, v.as_bytes()).filter(|_| self.key) {
+            Some(to) => self.inner.visit_str(to),
+            None => self.inner.visit_str(v),
+        }
+    }
+
+    fn visit_borrowed_str<E: de::Error>(self, v: &'de str) -> Result<V::Value, E> {
+        match renamed(self.renames, v.as_bytes()).filter(|_| self.key) {
+            Some(to) => self.inner.visit_borrowed_str(to),
+            None => self.inner.visit_borrowed_str(v),
+        }
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<V::Value, E> {
+        match renamed(self.renames, v.as_bytes()).filter(|_| self.key) {
+            Some(to) => self.inner.visit_borrowed_str(to),
+            None => self.inner.visit_string(v),
+        }
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<V::Value, E> {
+        match renamed(self.renames, v).filter(|_| self.key) {
+            Some(to) => self.inner.visit_borrowed_bytes(to.as_bytes()),
+            None => self.inner.visit_bytes(v),
+        }
+    }
+
+    fn visit_borrowed_bytes<E: de::Error>(self, v: &'de [u8]) -> Result<V::Value, E> {
+        match renamed(self.renames, v).filter(|_| self.key) {
+            Some(to) => self.inner.visit_borrowed_bytes(to.as_bytes()),
+            None => self.inner.visit_borrowed_bytes(v),
+        }
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<V::Value, E> {
+        match renamed(self.renames, &v).filter(|_| self.key) {
+            Some(to) => self.inner.visit_borrowed_bytes(to.as_bytes()),
+            None => self.inner.visit_byte_buf(v),
+        }
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<V::Value, E> {
+        self.inner.visit_none()
+    }
+
+    fn visit_some<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<V::Value, D::Error> {
+        self.inner.visit_some(deserializer)
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<V::Value, E> {
+        self.inner.visit_unit()
+    }
+
+    fn visit_newtype_struct<D: de::Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<V::Value, D::Error> {
+        self.inner.visit_newtype_struct(deserializer)
+    }
+
+    fn visit_seq<A: de::SeqAccess<'de>>(self, seq: A) -> Result<V::Value, A::Error> {
+        self.inner.visit_seq(seq)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<V::Value, A::Error> {
+        if self.key {
+            self.inner.visit_map(map)
+        } else {
+            self.inner.visit_map(Keys {
+                inner: map,
+                renames: self.renames,
+            })
+        }
+    }
+
+    fn visit_enum<A: de::EnumAccess<'de>>(self, data: A) -> Result<V::Value, A::Error> {
+        self.inner.visit_enum(data)
+    }
+}
+
+/// The entries of the top-level map, each key is deserialized through [`RenameKeys`]
+struct Keys<A> {
+    inner: A,
+    renames: &'static [(&'static str, &'static str)],
+}
+
+impl<'de, A: MapAccess<'de>> MapAccess<'de> for Keys<A> {
+    type Error = A::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        self.inner.next_key_seed(Key {
+            inner: seed,
+            renames: self.renames,
+        })
+    }
+
+    fn next_value_seed<S: DeserializeSeed<'de>>(
+        &mut self,
+        seed: S,
+    ) -> Result<S::Value, Self::Error> {
+        self.inner.next_value_seed(seed)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
+
+/// Deserializes a key of the top-level map under its new name
+struct Key<K> {
+    inner: K,
+    renames: &'static [(&'static str, &'static str)],
+}
+
+impl<'de, K: DeserializeSeed<'de>> DeserializeSeed<'de> for Key<K> {
+    type Value = K::Value;
+
+    fn deserialize<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<K::Value, D::Error> {
+        self.inner.deserialize(RenameKeys {
+            inner: deserializer,
+            renames: self.renames,
+            key: true,
+        })
+    }
+}
@@ -0,0 +1,60 @@
+use std::any::Any;
+use std::fmt::{Debug, Display};
+
+/// A failed migration along with the struct the input was originally deserialized into
+///
+/// Returned by [`crate::TryMigrate::try_from_str_migrations_salvage`] so callers can keep
+/// some of the old data instead of discarding all of it.
+pub struct Salvaged<E> {
+    /// The error from the conversion step that failed
+    pub error: E,
+    /// The [`crate::TryMigrate::version_name`] of the salvaged struct
+    pub version: &'static str,
+    /// `None` when the input didn't deserialize into the struct a second time
+    value: Option<Box<dyn Any>>,
+}
+
+impl<E> Salvaged<E> {
+    pub(crate) fn new(error: E, version: &'static str, value: Option<Box<dyn Any>>) -> Self {
+        Self {
+            error,
+            version,
+            value,
+        }
+    }
+
+    /// The salvaged struct if there is one and it is a `T`
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        self.value.as_ref()?.downcast_ref()
+    }
+
+    /// The salvaged struct if there is one and it is a `T`, otherwise returns `self`
+    pub fn downcast<T: Any>(self) -> Result<T, Self> {
+        match self.value.map(|value| value.downcast()) {
+            Some(Ok(value)) => Ok(*value),
+            Some(Err(value)) => Err(Self {
+                value: Some(value),
+                ..self
+            }),
+            None => Err(Self {
+                value: None,
+                ..self
+            }),
+        }
+    }
+}
+
+impl<E: Debug> Debug for Salvaged<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Salvaged")
+            .field("error", &self.error)
+            .field("version", &self.version)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<E: Display> Display for Salvaged<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (salvaged {})", self.error, self.version)
+    }
+}
@@ -0,0 +1,166 @@
+//! Example payloads for every struct in a chain
+
+use crate::walk::Matched;
+use crate::TryMigrate;
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::fmt::{Debug, Display};
+use std::marker::PhantomData;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+type Payload = Box<dyn Fn() -> Result<String, BoxError>>;
+
+/// Builds a serialized example payload for every struct in the chain of `T`
+///
+/// Register a sample for each version with [`Samples::sample`] or [`Samples::sample_default`],
+/// then [`Samples::payloads`] serializes them oldest first. Each payload is checked to
+/// deserialize into the struct it was made from, so they're safe to use as fixtures.
+///
+/// ```rust
+/// use magic_migrate::{Samples, TryMigrate};
+#[doc = include_str!("fixtures/try_personV1_V2.txt")]
+///
+/// magic_migrate::try_migrate_toml_chain!(
+///     error: PersonMigrationError,
+///     chain: [PersonV1, PersonV2],
+/// );
+///
+/// let samples = Samples::<PersonV2>::new()
+///     .sample::<PersonV1, _>(|| {
+///         toml::to_string(&PersonV1 {
+///             name: "Schneems".to_string(),
+///             title: Some("Dev".to_string()),
+///         })
+///     })
+///     .sample::<PersonV2, _>(|| {
+///         toml::to_string(&PersonV2 {
+///             name: "Schneems".to_string(),
+///             job_title: "Dev".to_string(),
+///         })
+///     });
+///
+/// let payloads = samples.payloads().unwrap();
+/// assert_eq!(payloads[0], ("PersonV1", "name = \"Schneems\"\ntitle = \"Dev\"\n".to_string()));
+/// assert_eq!(payloads[1].0, "PersonV2");
+///
+/// // Every sample migrates to the latest version
+/// for (_, payload) in payloads {
+///     assert!(PersonV2::try_from_str_migrations(&payload).unwrap().is_ok());
+/// }
+///
+/// let missing = Samples::<PersonV2>::new().payloads();
+/// assert!(matches!(missing, Err(magic_migrate::SampleError::Missing("PersonV1"))));
+/// ```
+pub struct Samples<T> {
+    payloads: HashMap<TypeId, Payload>,
+    latest: PhantomData<fn() -> T>,
+}
+
+impl<T: TryMigrate> Samples<T> {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            payloads: HashMap::new(),
+            latest: PhantomData,
+        }
+    }
+
+    /// Use the output of `payload` as the sample for `V`
+    #[must_use]
+    pub fn sample<V, E>(mut self, payload: impl Fn() -> Result<String, E> + 'static) -> Self
+    where
+        V: TryMigrate,
+        E: Into<BoxError>,
+    {
+        self.payloads.insert(
+            TypeId::of::<V>(),
+            Box::new(move || payload().map_err(Into::into)),
+        );
+        self
+    }
+
+    /// Use the [`Default`] value of `V`, serialized with `serialize`, as the sample for `V`
+    #[must_use]
+    pub fn sample_default<V, E>(self, serialize: impl Fn(&V) -> Result<String, E> + 'static) -> Self
+    where
+        V: TryMigrate + Default,
+        E: Into<BoxError>,
+    {
+        self.sample::<V, E>(move || serialize(&V::default()))
+    }
+
+    /// The [`TryMigrate::version_name`] and sample payload of every struct in the chain,
+    /// oldest first
+    pub fn payloads(&self) -> Result<Vec<(&'static str, String)>, SampleError> {
+        crate::walk::links::<T>()
+            .into_iter()
+            .map(|link| Ok((link.name, self.payload(&link)?)))
+            .collect()
+    }
+
+    /// The checked sample payload for one struct in the chain
+    pub(crate) fn payload(&self, link: &Matched) -> Result<String, SampleError> {
+        let payload = self
+            .payloads
+            .get(&link.type_id)
+            .ok_or(SampleError::Missing(link.name))?;
+        let payload = payload().map_err(|error| SampleError::Serialize(link.name, error))?;
+        if (link.deserialize)(&payload).is_none() {
+            return Err(SampleError::Mismatch(link.name));
+        }
+        Ok(payload)
+    }
+}
+
+impl<T: TryMigrate> Default for Samples<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: TryMigrate> Debug for Samples<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Samples")
+            .field("latest", &T::version_name())
+            .field("count", &self.payloads.len())
+            .finish()
+    }
+}
+
+/// Errors from building [`Samples::payloads`]
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum SampleError {
+    /// No sample was added for this struct in the chain
+    Missing(&'static str),
+    /// The sample could not be serialized
+    Serialize(&'static str, BoxError),
+    /// The payload does not deserialize into the struct it was made from
+    Mismatch(&'static str),
+}
+
+impl Display for SampleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SampleError::Missing(name) => write!(f, "No sample for `{name}`"),
+            SampleError::Serialize(name, error) => {
+                write!(f, "Could not serialize the sample for `{name}`: {error}")
+            }
+            SampleError::Mismatch(name) => {
+                write!(
+                    f,
+                    "The sample for `{name}` does not deserialize into `{name}`"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for SampleError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SampleError::Serialize(_, error) => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,660 @@
+//! Describe the serialized shape of a struct without a derive macro
+//!
+//! Serde's derived [`serde::Deserialize`] implementations tell the deserializer
+//! which struct, field, and variant names they expect. The [`Probe`] below is a
+//! deserializer that records those requests (and the type hints for each value)
+//! instead of parsing any input. Dummy values are fed back to the visitor so it
+//! keeps asking for the rest of the fields.
+//!
+//! Some types (such as `chrono::DateTime`) will reject the dummy value. When that
+//! happens the remaining values are recorded as [`Schema::Unknown`] while field names
+//! (which serde hands over all at once) are still captured. A `Deserialize` impl that
+//! panics on a dummy value is treated the same way, the panic is caught and the panic hook
+//! is silenced for the probing thread so nothing is printed.
+//!
+//! The result is best-effort: it's only as stable as the `Deserialize` impls are
+//! deterministic when given the same dummy values.
+
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, EnumAccess, MapAccess, SeqAccess, VariantAccess,
+    Visitor,
+};
+use std::cell::{Cell, RefCell};
+use std::fmt::Display;
+use std::panic::AssertUnwindSafe;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Nesting depth after which the probe stops descending, guards against recursive types
+const MAX_DEPTH: usize = 16;
+
+/// The serialized shape of a type as seen by its [`serde::Deserialize`] implementation
+///
+/// Built by [`crate::TryMigrate::schema`]. The [`Display`] output is used to compute
+/// [`crate::TryMigrate::schema_fingerprint`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Schema {
+    Bool,
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    F32,
+    F64,
+    Char,
+    Str,
+    Bytes,
+    Unit,
+    Option(Box<Schema>),
+    Seq(Box<Schema>),
+    Tuple(Vec<Schema>),
+    Map {
+        key: Box<Schema>,
+        value: Box<Schema>,
+    },
+    UnitStruct(&'static str),
+    Newtype {
+        name: &'static str,
+        inner: Box<Schema>,
+    },
+    TupleStruct {
+        name: &'static str,
+        fields: Vec<Schema>,
+    },
+    Struct {
+        name: &'static str,
+        fields: Vec<(&'static str, Schema)>,
+    },
+    Enum {
+        name: &'static str,
+        variants: &'static [&'static str],
+    },
+    /// The shape could not be determined, for example `#[serde(untagged)]` enums or
+    /// values that come after a field that rejected the probe's placeholder value.
+    Unknown,
+}
+
+impl Schema {
+    /// Record the shape of `T`
+    pub(crate) fn of<T: DeserializeOwned>() -> Self {
+        let placeholders = Placeholders::default();
+        quietly(|| loop {
+            let mut schema = Schema::Unknown;
+            let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
+                T::deserialize(Probe {
+                    slot: &mut schema,
+                    depth: 0,
+                    placeholders: &placeholders,
+                })
+            }));
+            // An error or panic only means the probe could not descend all the way, what was
+            // recorded is kept
+            match result {
+                Ok(Err(_)) if placeholders.advance() => {}
+                _ => return schema,
+            }
+        })
+    }
+}
+
+/// Run `f` with the panic hook silenced on the current thread
+///
+/// The hook is process-global. It's swapped for one that forwards panics from every other
+/// thread to the previous hook, and restored afterwards. Swaps are serialized so each one
+/// restores the hook it replaced, a hook set by another thread while `f` runs is lost.
+fn quietly<R>(f: impl FnOnce() -> R) -> R {
+    static SWAP: Mutex<()> = Mutex::new(());
+    let _swap = SWAP.lock().unwrap_or_else(PoisonError::into_inner);
+
+    let quiet = std::thread::current().id();
+    let previous = Arc::new(std::panic::take_hook());
+    let forward = Arc::clone(&previous);
+    std::panic::set_hook(Box::new(move |info| {
+        if std::thread::current().id() != quiet {
+            forward(info);
+        }
+    }));
+
+    let result = f();
+
+    // Dropping the quiet hook releases its handle on the previous hook
+    drop(std::panic::take_hook());
+    if let Ok(previous) = Arc::try_unwrap(previous) {
+        std::panic::set_hook(previous);
+    }
+    result
+}
+
+fn write_list<T: Display>(f: &mut std::fmt::Formatter<'_>, items: &[T]) -> std::fmt::Result {
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            f.write_str(", ")?;
+        }
+        write!(f, "{item}")?;
+    }
+    Ok(())
+}
+
+impl Display for Schema {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Schema::Bool => f.write_str("bool"),
+            Schema::I8 => f.write_str("i8"),
+            Schema::I16 => f.write_str("i16"),
+            Schema::I32 => f.write_str("i32"),
+            Schema::I64 => f.write_str("i64"),
+            Schema::I128 => f.write_str("i128"),
+            Schema::U8 => f.write_str("u8"),
+            Schema::U16 => f.write_str("u16"),
+            Schema::U32 => f.write_str("u32"),
+            Schema::U64 => f.write_str("u64"),
+            Schema::U128 => f.write_str("u128"),
+            Schema::F32 => f.write_str("f32"),
+            Schema::F64 => f.write_str("f64"),
+            Schema::Char => f.write_str("char"),
+            Schema::Str => f.write_str("str"),
+            Schema::Bytes => f.write_str("bytes"),
+            Schema::Unit => f.write_str("()"),
+            Schema::Option(inner) => write!(f, "option<{inner}>"),
+            Schema::Seq(inner) => write!(f, "seq<{inner}>"),
+            Schema::Tuple(items) => {
+                f.write_str("(")?;
+                write_list(f, items)?;
+                f.write_str(")")
+            }
+            Schema::Map { key, value } => write!(f, "map<{key}, {value}>"),
+            Schema::UnitStruct(name) => f.write_str(name),
+            Schema::Newtype { name, inner } => write!(f, "{name}({inner})"),
+            Schema::TupleStruct { name, fields } => {
+                write!(f, "{name}(")?;
+                write_list(f, fields)?;
+                f.write_str(")")
+            }
+            Schema::Struct { name, fields } => {
+                write!(f, "{name} {{")?;
+                for (i, (field, schema)) in fields.iter().enumerate() {
+                    let sep = if i > 0 { "," } else { "" };
+                    write!(f, "{sep} {field}: {schema}")?;
+                }
+                f.write_str(" }")
+            }
+            Schema::Enum { name, variants } => {
+                write!(f, "enum {name} {{ ")?;
+                write_list(f, variants)?;
+                f.write_str(" }")
+            }
+            Schema::Unknown => f.write_str("?"),
+        }
+    }
+}
+
+/// FNV-1a, used so fingerprints don't change between Rust releases like `DefaultHasher` may
+pub(crate) fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+pub(crate) const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+
+#[derive(Debug)]
+struct ProbeError;
+
+impl Display for ProbeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("schema probe cannot continue")
+    }
+}
+
+impl std::error::Error for ProbeError {}
+
+impl de::Error for ProbeError {
+    fn custom<T: Display>(_msg: T) -> Self {
+        ProbeError
+    }
+}
+
+/// Placeholder values handed to visitors, tried in order when a visitor rejects one
+const STRINGS: &[&str] = &[
+    "",
+    "0",
+    "1970-01-01T00:00:00Z",
+    "1970-01-01",
+    "00:00:00",
+    "0.0.0",
+    "00000000-0000-0000-0000-000000000000",
+    "a",
+];
+
+/// Tracks which placeholder to hand out for each primitive value in visit order
+///
+/// When a visitor rejects a placeholder (`chrono::DateTime` won't accept `""`) the probe
+/// is run again with the next candidate for that value so later fields can be recorded.
+#[derive(Default)]
+struct Placeholders {
+    choices: RefCell<Vec<usize>>,
+    cursor: Cell<usize>,
+    rejected: Cell<Option<(usize, usize)>>,
+}
+
+impl Placeholders {
+    /// Returns the position of the value being visited and which candidate to use for it
+    fn next(&self) -> (usize, usize) {
+        let position = self.cursor.get();
+        self.cursor.set(position + 1);
+        let choice = self.choices.borrow().get(position).copied().unwrap_or(0);
+        (position, choice)
+    }
+
+    fn reject(&self, position: usize, candidates: usize) {
+        if self.rejected.get().is_none() {
+            self.rejected.set(Some((position, candidates)));
+        }
+    }
+
+    /// Prepare for another run, returns false when there are no more candidates to try
+    fn advance(&self) -> bool {
+        self.cursor.set(0);
+        match self.rejected.take() {
+            Some((position, candidates)) => {
+                let mut choices = self.choices.borrow_mut();
+                choices.resize(position + 1, 0);
+                choices[position] += 1;
+                choices[position] < candidates
+            }
+            None => false,
+        }
+    }
+}
+
+/// A deserializer that writes what it was asked for into `slot`
+struct Probe<'a> {
+    slot: &'a mut Schema,
+    depth: usize,
+    placeholders: &'a Placeholders,
+}
+
+impl Probe<'_> {
+    fn nested<'b>(slot: &'b mut Schema, depth: usize, placeholders: &'b Placeholders) -> Probe<'b> {
+        Probe {
+            slot,
+            depth: depth + 1,
+            placeholders,
+        }
+    }
+}
+
+macro_rules! probe_primitive {
+    ($($method:ident => $variant:ident, $visit:ident($candidates:expr);)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                *self.slot = Schema::$variant;
+                let candidates = $candidates;
+                let (position, choice) = self.placeholders.next();
+                let value = candidates.get(choice).copied().unwrap_or(candidates[0]);
+                visitor.$visit(value).inspect_err(|_| {
+                    self.placeholders.reject(position, candidates.len());
+                })
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for Probe<'_> {
+    type Error = ProbeError;
+
+    probe_primitive! {
+        deserialize_bool => Bool, visit_bool(&[false, true]);
+        deserialize_i8 => I8, visit_i8(&[0, 1]);
+        deserialize_i16 => I16, visit_i16(&[0, 1]);
+        deserialize_i32 => I32, visit_i32(&[0, 1]);
+        deserialize_i64 => I64, visit_i64(&[0, 1]);
+        deserialize_i128 => I128, visit_i128(&[0, 1]);
+        deserialize_u8 => U8, visit_u8(&[0, 1]);
+        deserialize_u16 => U16, visit_u16(&[0, 1]);
+        deserialize_u32 => U32, visit_u32(&[0, 1]);
+        deserialize_u64 => U64, visit_u64(&[0, 1]);
+        deserialize_u128 => U128, visit_u128(&[0, 1]);
+        deserialize_f32 => F32, visit_f32(&[0.0, 1.0]);
+        deserialize_f64 => F64, visit_f64(&[0.0, 1.0]);
+        deserialize_char => Char, visit_char(&[' ', 'a']);
+        deserialize_str => Str, visit_str(STRINGS);
+        deserialize_string => Str, visit_str(STRINGS);
+        deserialize_bytes => Bytes, visit_bytes(&[&[]]);
+        deserialize_byte_buf => Bytes, visit_bytes(&[&[]]);
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        *self.slot = Schema::Unit;
+        visitor.visit_unit()
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        *self.slot = Schema::Unit;
+        visitor.visit_unit()
+    }
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+        *self.slot = Schema::Unknown;
+        Err(ProbeError)
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        *self.slot = Schema::Option(Box::new(Schema::Unknown));
+        match self.slot {
+            Schema::Option(inner) if self.depth < MAX_DEPTH => {
+                visitor.visit_some(Probe::nested(inner, self.depth, self.placeholders))
+            }
+            _ => visitor.visit_none(),
+        }
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        *self.slot = Schema::UnitStruct(name);
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        *self.slot = Schema::Newtype {
+            name,
+            inner: Box::new(Schema::Unknown),
+        };
+        match self.slot {
+            Schema::Newtype { inner, .. } if self.depth < MAX_DEPTH => {
+                visitor.visit_newtype_struct(Probe::nested(inner, self.depth, self.placeholders))
+            }
+            _ => Err(ProbeError),
+        }
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        *self.slot = Schema::Seq(Box::new(Schema::Unknown));
+        match self.slot {
+            Schema::Seq(inner) if self.depth < MAX_DEPTH => visitor.visit_seq(SlotsAccess {
+                slots: std::slice::from_mut(inner.as_mut()).iter_mut(),
+                depth: self.depth,
+                placeholders: self.placeholders,
+            }),
+            _ => visitor.visit_seq(SlotsAccess {
+                slots: [].iter_mut(),
+                depth: self.depth,
+                placeholders: self.placeholders,
+            }),
+        }
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        *self.slot = Schema::Tuple(vec![Schema::Unknown; len]);
+        match self.slot {
+            Schema::Tuple(items) if self.depth < MAX_DEPTH => visitor.visit_seq(SlotsAccess {
+                slots: items.iter_mut(),
+                depth: self.depth,
+                placeholders: self.placeholders,
+            }),
+            _ => Err(ProbeError),
+        }
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        *self.slot = Schema::TupleStruct {
+            name,
+            fields: vec![Schema::Unknown; len],
+        };
+        match self.slot {
+            Schema::TupleStruct { fields, .. } if self.depth < MAX_DEPTH => {
+                visitor.visit_seq(SlotsAccess {
+                    slots: fields.iter_mut(),
+                    depth: self.depth,
+                    placeholders: self.placeholders,
+                })
+            }
+            _ => Err(ProbeError),
+        }
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        *self.slot = Schema::Map {
+            key: Box::new(Schema::Unknown),
+            value: Box::new(Schema::Unknown),
+        };
+        match self.slot {
+            Schema::Map { key, value } if self.depth < MAX_DEPTH => {
+                visitor.visit_map(EntryAccess {
+                    entry: Some((key, value)),
+                    value: None,
+                    depth: self.depth,
+                    placeholders: self.placeholders,
+                })
+            }
+            _ => visitor.visit_map(EntryAccess {
+                entry: None,
+                value: None,
+                depth: self.depth,
+                placeholders: self.placeholders,
+            }),
+        }
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        *self.slot = Schema::Struct {
+            name,
+            fields: fields.iter().map(|f| (*f, Schema::Unknown)).collect(),
+        };
+        match self.slot {
+            Schema::Struct { fields, .. } if self.depth < MAX_DEPTH => {
+                visitor.visit_map(FieldAccess {
+                    fields: fields.iter_mut(),
+                    value: None,
+                    depth: self.depth,
+                    placeholders: self.placeholders,
+                })
+            }
+            _ => Err(ProbeError),
+        }
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        *self.slot = Schema::Enum { name, variants };
+        match variants.first() {
+            Some(variant) if self.depth < MAX_DEPTH => visitor.visit_enum(VariantProbe {
+                variant,
+                depth: self.depth,
+                placeholders: self.placeholders,
+            }),
+            _ => Err(ProbeError),
+        }
+    }
+}
+
+/// Feeds one probed element per slot, for sequences and tuples
+struct SlotsAccess<'a> {
+    slots: std::slice::IterMut<'a, Schema>,
+    depth: usize,
+    placeholders: &'a Placeholders,
+}
+
+impl<'de> SeqAccess<'de> for SlotsAccess<'_> {
+    type Error = ProbeError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Self::Error> {
+        match self.slots.next() {
+            Some(slot) => seed
+                .deserialize(Probe::nested(slot, self.depth, self.placeholders))
+                .map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Feeds a single probed key/value pair, for maps
+struct EntryAccess<'a> {
+    entry: Option<(&'a mut Schema, &'a mut Schema)>,
+    value: Option<&'a mut Schema>,
+    depth: usize,
+    placeholders: &'a Placeholders,
+}
+
+impl<'de> MapAccess<'de> for EntryAccess<'_> {
+    type Error = ProbeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        match self.entry.take() {
+            Some((key, value)) => {
+                self.value = Some(value);
+                seed.deserialize(Probe::nested(key, self.depth, self.placeholders))
+                    .map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, Self::Error> {
+        let slot = self.value.take().ok_or(ProbeError)?;
+        seed.deserialize(Probe::nested(slot, self.depth, self.placeholders))
+    }
+}
+
+/// Feeds every field name serde asked for followed by a probed value
+struct FieldAccess<'a> {
+    fields: std::slice::IterMut<'a, (&'static str, Schema)>,
+    value: Option<&'a mut Schema>,
+    depth: usize,
+    placeholders: &'a Placeholders,
+}
+
+impl<'de> MapAccess<'de> for FieldAccess<'_> {
+    type Error = ProbeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        match self.fields.next() {
+            Some((name, value)) => {
+                self.value = Some(value);
+                seed.deserialize(de::value::StrDeserializer::<ProbeError>::new(name))
+                    .map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, Self::Error> {
+        let slot = self.value.take().ok_or(ProbeError)?;
+        seed.deserialize(Probe::nested(slot, self.depth, self.placeholders))
+    }
+}
+
+/// Selects the first variant of an enum, payloads are probed but not recorded
+struct VariantProbe<'a> {
+    variant: &'static str,
+    depth: usize,
+    placeholders: &'a Placeholders,
+}
+
+impl<'de> EnumAccess<'de> for VariantProbe<'_> {
+    type Error = ProbeError;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self::Variant), Self::Error> {
+        let value =
+            seed.deserialize(de::value::StrDeserializer::<ProbeError>::new(self.variant))?;
+        Ok((value, self))
+    }
+}
+
+impl<'de> VariantAccess<'de> for VariantProbe<'_> {
+    type Error = ProbeError;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, Self::Error> {
+        seed.deserialize(Probe::nested(
+            &mut Schema::Unknown,
+            self.depth,
+            self.placeholders,
+        ))
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        de::Deserializer::deserialize_tuple(
+            Probe::nested(&mut Schema::Unknown, self.depth, self.placeholders),
+            len,
+            visitor,
+        )
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        de::Deserializer::deserialize_struct(
+            Probe::nested(&mut Schema::Unknown, self.depth, self.placeholders),
+            "",
+            fields,
+            visitor,
+        )
+    }
+}
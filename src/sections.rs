@@ -0,0 +1,171 @@
+//! Migrate each top-level table of a TOML document with its own chain
+
+use crate::error::write_report;
+use crate::{MigrateErrorKind, TryMigrate};
+use serde::Serialize;
+use std::error::Error;
+use std::fmt::{Debug, Display};
+
+type Migration = Box<dyn Fn(&str) -> Result<toml::Value, SectionError>>;
+
+/// Runs each top-level table of a TOML document through a different migration chain
+///
+/// Tables without a registered chain are kept as is, registered tables that are missing
+/// from the document are skipped.
+///
+/// Each table is serialized on its own before it's run through its chain, so
+/// `toml::Spanned` fields in a section's structs point into that serialized table instead
+/// of the original document.
+///
+/// ```rust
+/// use magic_migrate::{TomlSections, TryMigrate};
+#[doc = include_str!("fixtures/try_personV1_V2.txt")]
+///
+/// magic_migrate::try_migrate_toml_chain!(
+///     error: PersonMigrationError,
+///     chain: [PersonV1, PersonV2],
+/// );
+///
+/// let sections = TomlSections::new().section::<PersonV2>("owner");
+///
+/// let table = sections
+///     .migrate(
+///         r#"
+///         [owner]
+///         name = "Schneems"
+///         title = "Dev"
+///
+///         [env]
+///         RUST_LOG = "debug"
+///         "#,
+///     )
+///     .unwrap();
+///
+/// assert_eq!(table["owner"]["job_title"].as_str(), Some("Dev"));
+/// assert_eq!(table["env"]["RUST_LOG"].as_str(), Some("debug"));
+/// ```
+#[derive(Default)]
+pub struct TomlSections {
+    sections: Vec<(String, Migration)>,
+}
+
+impl TomlSections {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Migrate the top-level table `key` with the chain of `T`
+    ///
+    /// The table is always deserialized as TOML, the chain's own
+    /// [`TryMigrate::deserializer`] is not used.
+    #[must_use]
+    pub fn section<T>(mut self, key: impl Into<String>) -> Self
+    where
+        T: TryMigrate + Serialize,
+    {
+        let key = key.into();
+        let name = key.clone();
+        self.sections.push((
+            key,
+            Box::new(move |input| {
+                let latest = T::try_from_str_migrations_as::<crate::Toml>(input)
+                    .ok_or_else(|| SectionError::NoMatchingVersion(name.clone()))?
+                    .map_err(|error| SectionError::Migrate(name.clone(), error.to_string()))?;
+                toml::Value::try_from(latest)
+                    .map_err(|error| SectionError::Serialize(name.clone(), error))
+            }),
+        ));
+        self
+    }
+
+    /// Parse `input` and migrate every registered table
+    pub fn migrate(&self, input: &str) -> Result<toml::Table, SectionError> {
+        let mut table: toml::Table = input.parse().map_err(SectionError::Parse)?;
+        for (key, migrate) in &self.sections {
+            if let Some(value) = table.get(key) {
+                let section = toml::to_string(value)
+                    .map_err(|error| SectionError::Serialize(key.clone(), error))?;
+                let migrated = migrate(&section)?;
+                table.insert(key.clone(), migrated);
+            }
+        }
+        Ok(table)
+    }
+}
+
+impl Debug for TomlSections {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TomlSections")
+            .field(
+                "sections",
+                &self.sections.iter().map(|(key, _)| key).collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+/// Errors from [`TomlSections::migrate`]
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum SectionError {
+    /// The document is not valid TOML
+    Parse(toml::de::Error),
+    /// No struct in the chain could deserialize the table
+    NoMatchingVersion(String),
+    /// A struct deserialized but migrating it to the latest version failed, the error's
+    /// message is kept since each section's chain has its own error type
+    Migrate(String, String),
+    /// The table could not be serialized
+    Serialize(String, toml::ser::Error),
+}
+
+impl SectionError {
+    #[must_use]
+    pub fn kind(&self) -> MigrateErrorKind {
+        match self {
+            SectionError::Parse(_) => MigrateErrorKind::Format,
+            SectionError::NoMatchingVersion(_) => MigrateErrorKind::NoMatchingVersion,
+            SectionError::Migrate(..) => MigrateErrorKind::Migrate,
+            SectionError::Serialize(..) => MigrateErrorKind::Serialize,
+        }
+    }
+}
+
+impl Display for SectionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SectionError::Parse(error) => write_report(
+                f,
+                format_args!("Could not parse TOML"),
+                error,
+                error.source(),
+            ),
+            SectionError::NoMatchingVersion(key) => {
+                write!(f, "No version in the chain matches the [{key}] table")
+            }
+            SectionError::Migrate(key, error) => write_report(
+                f,
+                format_args!("Could not migrate the [{key}] table"),
+                error,
+                None,
+            ),
+            SectionError::Serialize(key, error) => write_report(
+                f,
+                format_args!("Could not serialize the [{key}] table"),
+                error,
+                error.source(),
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SectionError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SectionError::Parse(error) => Some(error),
+            SectionError::Serialize(_, error) => Some(error),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use crate::{MigrateError, MigrateErrorKind};
+use std::fmt::Display;
+
+/// An error along with the start of the input that caused it
+///
+/// Returned by [`crate::TryMigrate::try_from_str_migrations_with_snippet`] so a failure in
+/// production can be reproduced from the logs. The snippet is cut at a character boundary
+/// and never longer than the limit it was made with. Secrets are removed from the snippet
+/// with [`crate::TryMigrate::redact_input`], the error is kept as is.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct WithSnippet<E> {
+    /// The error
+    pub error: E,
+    /// The start of the redacted input
+    pub snippet: String,
+    /// The length of the whole redacted input in bytes
+    pub input_len: usize,
+}
+
+impl<E> WithSnippet<E> {
+    /// Keep up to `max_chars` characters of `input` with `error`
+    pub(crate) fn new(error: E, input: &str, max_chars: usize) -> Self {
+        let end = input
+            .char_indices()
+            .nth(max_chars)
+            .map_or(input.len(), |(index, _)| index);
+        Self {
+            error,
+            snippet: input[..end].to_string(),
+            input_len: input.len(),
+        }
+    }
+
+    /// True when the snippet is only the start of the input
+    #[must_use]
+    pub fn is_truncated(&self) -> bool {
+        self.snippet.len() < self.input_len
+    }
+}
+
+impl<E> WithSnippet<MigrateError<E>> {
+    #[must_use]
+    pub fn kind(&self) -> MigrateErrorKind {
+        self.error.kind()
+    }
+}
+
+impl<E: Display> Display for WithSnippet<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ellipsis = if self.is_truncated() { "..." } else { "" };
+        if f.alternate() {
+            write!(
+                f,
+                "{:#}\n\nInput of {} bytes:\n    {:?}{ellipsis}",
+                self.error, self.input_len, self.snippet
+            )
+        } else {
+            write!(
+                f,
+                "{} (input of {} bytes: {:?}{ellipsis})",
+                self.error, self.input_len, self.snippet
+            )
+        }
+    }
+}
+
+impl<E: std::error::Error + 'static> std::error::Error for WithSnippet<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
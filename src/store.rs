@@ -0,0 +1,213 @@
+//! Migrate values from a key-value store as they are read
+
+use crate::error::write_report;
+use crate::{MigrateErrorKind, TryMigrate};
+use std::any::TypeId;
+use std::error::Error;
+use std::fmt::{Debug, Display};
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+type Serializer<T> = Box<dyn Fn(&T) -> Result<String, BoxError>>;
+
+/// A minimal byte oriented key-value store, such as `sled` or `redb`
+pub trait StorageBackend {
+    type Error;
+
+    fn get(&self, key: &str) -> Result<Option<Vec<u8>>, Self::Error>;
+
+    fn put(&self, key: &str, value: &[u8]) -> Result<(), Self::Error>;
+}
+
+/// Errors from reading a value out of a [`MigratingStore`]
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum StoreError<B, E> {
+    /// The backend failed to read or write
+    Backend(B),
+    /// The stored bytes are not valid UTF-8
+    NotUtf8(String, std::str::Utf8Error),
+    /// No struct in the chain could deserialize the stored value
+    NoMatchingVersion(String),
+    /// A struct deserialized but migrating it to the latest version failed
+    Migrate(String, E),
+    /// The migrated value could not be serialized for read-repair
+    Repair(String, BoxError),
+}
+
+impl<B, E> StoreError<B, E> {
+    #[must_use]
+    pub fn kind(&self) -> MigrateErrorKind {
+        match self {
+            StoreError::Backend(_) => MigrateErrorKind::Io,
+            StoreError::NotUtf8(..) => MigrateErrorKind::Format,
+            StoreError::NoMatchingVersion(_) => MigrateErrorKind::NoMatchingVersion,
+            StoreError::Migrate(..) => MigrateErrorKind::Migrate,
+            StoreError::Repair(..) => MigrateErrorKind::Serialize,
+        }
+    }
+}
+
+impl<B: Display, E: Display> Display for StoreError<B, E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StoreError::Backend(error) => {
+                write_report(f, format_args!("Storage backend error"), error, None)
+            }
+            StoreError::NotUtf8(key, error) => write_report(
+                f,
+                format_args!("Value for {key:?} is not UTF-8"),
+                error,
+                error.source(),
+            ),
+            StoreError::NoMatchingVersion(key) => {
+                write!(f, "No version in the chain matches the value for {key:?}")
+            }
+            StoreError::Migrate(key, error) => write_report(
+                f,
+                format_args!("Could not migrate the value for {key:?}"),
+                error,
+                None,
+            ),
+            StoreError::Repair(key, error) => write_report(
+                f,
+                format_args!("Could not serialize the value for {key:?}"),
+                error,
+                error.source(),
+            ),
+        }
+    }
+}
+
+impl<B, E> std::error::Error for StoreError<B, E>
+where
+    B: std::error::Error + 'static,
+    E: Display + Debug,
+{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            StoreError::Backend(error) => Some(error),
+            StoreError::NotUtf8(_, error) => Some(error),
+            StoreError::Repair(_, error) => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// Wraps a [`StorageBackend`] so values are run through the migration chain of `T` when read
+///
+/// With [`MigratingStore::read_repair`] values that were stored as an older version are
+/// written back as the latest version, so each value only needs to be migrated once.
+///
+/// ```rust
+/// use magic_migrate::{MigratingStore, StorageBackend, TryMigrate};
+/// use std::cell::RefCell;
+/// use std::collections::HashMap;
+#[doc = include_str!("fixtures/try_personV1_V2.txt")]
+///
+/// magic_migrate::try_migrate_toml_chain!(
+///     error: PersonMigrationError,
+///     chain: [PersonV1, PersonV2],
+/// );
+///
+/// #[derive(Default)]
+/// struct Memory(RefCell<HashMap<String, Vec<u8>>>);
+///
+/// impl StorageBackend for Memory {
+///     type Error = std::convert::Infallible;
+///
+///     fn get(&self, key: &str) -> Result<Option<Vec<u8>>, Self::Error> {
+///         Ok(self.0.borrow().get(key).cloned())
+///     }
+///
+///     fn put(&self, key: &str, value: &[u8]) -> Result<(), Self::Error> {
+///         self.0.borrow_mut().insert(key.to_string(), value.to_vec());
+///         Ok(())
+///     }
+/// }
+///
+/// let memory = Memory::default();
+/// memory.put("richard", b"name = 'Schneems'\ntitle = 'Dev'").unwrap();
+///
+/// let store = MigratingStore::<PersonV2, _>::new(memory).read_repair(toml::to_string);
+/// let person = store.get("richard").unwrap().unwrap();
+/// assert_eq!(person.job_title, "Dev".to_string());
+///
+/// // The latest version was written back
+/// let stored = store.backend().get("richard").unwrap().unwrap();
+/// assert_eq!(String::from_utf8(stored).unwrap(), toml::to_string(&person).unwrap());
+///
+/// assert!(store.get("missing").unwrap().is_none());
+/// ```
+pub struct MigratingStore<T, S> {
+    backend: S,
+    read_repair: Option<Serializer<T>>,
+}
+
+impl<T, S> MigratingStore<T, S>
+where
+    T: TryMigrate,
+    S: StorageBackend,
+{
+    pub fn new(backend: S) -> Self {
+        Self {
+            backend,
+            read_repair: None,
+        }
+    }
+
+    /// Write values that were stored as an older version back using `serialize`
+    #[must_use]
+    pub fn read_repair<F, E>(mut self, serialize: F) -> Self
+    where
+        F: Fn(&T) -> Result<String, E> + 'static,
+        E: Into<BoxError>,
+    {
+        self.read_repair = Some(Box::new(move |value| serialize(value).map_err(Into::into)));
+        self
+    }
+
+    pub fn backend(&self) -> &S {
+        &self.backend
+    }
+
+    pub fn into_backend(self) -> S {
+        self.backend
+    }
+
+    /// Read and migrate the value for `key`, returns `Ok(None)` when the key is not stored
+    pub fn get(
+        &self,
+        key: &str,
+    ) -> Result<Option<T>, StoreError<S::Error, <T as TryMigrate>::Error>> {
+        let Some(bytes) = self.backend.get(key).map_err(StoreError::Backend)? else {
+            return Ok(None);
+        };
+        let input =
+            std::str::from_utf8(&bytes).map_err(|error| StoreError::NotUtf8(key.into(), error))?;
+
+        let (matched, result) = crate::walk::walk::<T>(input)
+            .ok_or_else(|| StoreError::NoMatchingVersion(key.into()))?;
+        let latest = result.map_err(|error| StoreError::Migrate(key.into(), error))?;
+
+        if let Some(serialize) = &self.read_repair {
+            if matched.type_id != TypeId::of::<T>() {
+                let repaired =
+                    serialize(&latest).map_err(|error| StoreError::Repair(key.into(), error))?;
+                self.backend
+                    .put(key, repaired.as_bytes())
+                    .map_err(StoreError::Backend)?;
+            }
+        }
+
+        Ok(Some(latest))
+    }
+}
+
+impl<T, S: Debug> Debug for MigratingStore<T, S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MigratingStore")
+            .field("backend", &self.backend)
+            .field("read_repair", &self.read_repair.is_some())
+            .finish()
+    }
+}
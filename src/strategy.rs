@@ -0,0 +1,139 @@
+//! Pick which structs in a chain are tried, and in what order
+
+use std::any::TypeId;
+
+/// Decides which structs in a chain are tried, and in what order, when deserializing input
+///
+/// Used by [`crate::TryMigrate::try_from_str_migrations_with_strategy`]. The first version
+/// returned by [`ResolutionStrategy::order`] that the input deserializes into is migrated
+/// to the latest version, the rest are not tried. Versions are [`Candidate`] handles so
+/// structs that share a [`crate::TryMigrate::version_name`] are still told apart.
+///
+/// ```rust
+/// use magic_migrate::{Candidate, ResolutionStrategy, TryMigrate};
+#[doc = include_str!("fixtures/try_personV1_V2.txt")]
+///
+/// magic_migrate::try_migrate_toml_chain!(
+///     error: PersonMigrationError,
+///     chain: [PersonV1, PersonV2],
+/// );
+///
+/// /// Only tries the version named in a leading `# version: ` comment
+/// struct Tagged;
+///
+/// impl ResolutionStrategy for Tagged {
+///     fn order(&self, input: &str, versions: &[Candidate]) -> Vec<Candidate> {
+///         let tag = input.lines().next().and_then(|line| line.strip_prefix("# version: "));
+///         versions.iter().copied().filter(|version| Some(version.name()) == tag).collect()
+///     }
+/// }
+///
+/// let input = "# version: PersonV1\nname = 'Schneems'\ntitle = 'Dev'";
+/// let person = PersonV2::try_from_str_migrations_with_strategy(input, &Tagged)
+///     .unwrap()
+///     .unwrap();
+/// assert_eq!(person.job_title, "Dev".to_string());
+///
+/// let input = "# version: PersonV2\nname = 'Schneems'\ntitle = 'Dev'";
+/// assert!(PersonV2::try_from_str_migrations_with_strategy(input, &Tagged).is_none());
+/// ```
+pub trait ResolutionStrategy {
+    /// The versions to try, `versions` is every struct in the chain from newest to oldest
+    fn order(&self, input: &str, versions: &[Candidate]) -> Vec<Candidate>;
+}
+
+/// A struct in a chain that a [`ResolutionStrategy`] can pick
+///
+/// ```rust
+/// use magic_migrate::{Candidate, ResolutionStrategy, TryMigrate};
+/// use std::any::TypeId;
+///
+/// mod v1 {
+///     #[derive(serde::Deserialize, Debug)]
+///     pub struct Metadata {
+///         pub ruby: String,
+///     }
+/// }
+///
+/// mod v2 {
+///     #[derive(serde::Deserialize, Debug)]
+///     pub struct Metadata {
+///         pub ruby: String,
+///     }
+/// }
+///
+/// use v1::Metadata as MetadataV1;
+/// use v2::Metadata as MetadataV2;
+///
+/// impl From<MetadataV1> for MetadataV2 {
+///     fn from(value: MetadataV1) -> Self {
+///         MetadataV2 { ruby: format!("{} (from v1)", value.ruby) }
+///     }
+/// }
+///
+/// magic_migrate::try_migrate_toml_chain!(chain: [MetadataV1, MetadataV2]);
+///
+/// /// Only tries the first struct in the chain
+/// struct Original;
+///
+/// impl ResolutionStrategy for Original {
+///     fn order(&self, _input: &str, versions: &[Candidate]) -> Vec<Candidate> {
+///         versions
+///             .iter()
+///             .copied()
+///             .filter(|version| version.type_id() == TypeId::of::<MetadataV1>())
+///             .collect()
+///     }
+/// }
+///
+/// assert_eq!(MetadataV1::version_name(), MetadataV2::version_name());
+/// let metadata = MetadataV2::try_from_str_migrations_with_strategy("ruby = '3.4'", &Original)
+///     .unwrap()
+///     .unwrap();
+/// assert_eq!(metadata.ruby, "3.4 (from v1)");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Candidate {
+    name: &'static str,
+    type_id: TypeId,
+}
+
+impl Candidate {
+    pub(crate) fn new(name: &'static str, type_id: TypeId) -> Self {
+        Candidate { name, type_id }
+    }
+
+    /// The struct's [`crate::TryMigrate::version_name`], which isn't unique when two
+    /// structs share a name in different modules
+    #[must_use]
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// The struct's [`TypeId`], unique within the chain
+    #[must_use]
+    pub fn type_id(&self) -> TypeId {
+        self.type_id
+    }
+}
+
+/// Try the latest version first then each older one, which is what
+/// [`crate::TryMigrate::try_from_str_migrations`] does
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NewestFirst;
+
+impl ResolutionStrategy for NewestFirst {
+    fn order(&self, _input: &str, versions: &[Candidate]) -> Vec<Candidate> {
+        versions.to_vec()
+    }
+}
+
+/// Try the first struct in the chain first then each newer one
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OldestFirst;
+
+impl ResolutionStrategy for OldestFirst {
+    fn order(&self, _input: &str, versions: &[Candidate]) -> Vec<Candidate> {
+        versions.iter().rev().copied().collect()
+    }
+}
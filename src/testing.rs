@@ -0,0 +1,474 @@
+//! Helpers for testing a migration chain
+
+use crate::{Omitting, Schema, TryMigrate};
+use serde::Serialize;
+use std::any::TypeId;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+
+/// Assert that every file in `dir` migrates to `T`, returns the version each one was
+/// detected as
+///
+/// Use it to keep a corpus of real payloads exercising the chain. Files are read in name
+/// order. A file whose name starts with a
+/// [`TryMigrate::version_name`] followed by `.`, `_`, or `-` (such as `PersonV1_empty.toml`)
+/// must be detected as that version.
+///
+/// # Panics
+///
+/// When the directory can't be read, or any file fails to read, match, or migrate. Every
+/// failure is listed in the message, followed by the version each passing file was
+/// detected as.
+///
+/// ```rust
+/// use magic_migrate::TryMigrate;
+#[doc = include_str!("fixtures/try_personV1_V2.txt")]
+///
+/// magic_migrate::try_migrate_toml_chain!(
+///     error: PersonMigrationError,
+///     chain: [PersonV1, PersonV2],
+/// );
+///
+/// let dir = std::env::temp_dir().join("magic_migrate_assert_fixtures_doctest");
+/// std::fs::create_dir_all(&dir).unwrap();
+/// std::fs::write(dir.join("PersonV1.toml"), "name = 'Schneems'\ntitle = 'Dev'").unwrap();
+/// std::fs::write(dir.join("PersonV2_dev.toml"), "name = 'Schneems'\njob_title = 'Dev'").unwrap();
+///
+/// let detected = magic_migrate::assert_fixtures_migrate::<PersonV2>(&dir);
+/// assert_eq!(
+///     detected.iter().map(|(_, version)| *version).collect::<Vec<_>>(),
+///     vec!["PersonV1", "PersonV2"]
+/// );
+/// ```
+pub fn assert_fixtures_migrate<T: TryMigrate>(
+    dir: impl AsRef<Path>,
+) -> Vec<(PathBuf, &'static str)> {
+    let dir = dir.as_ref();
+    let mut paths = std::fs::read_dir(dir)
+        .unwrap_or_else(|error| panic!("Could not read {}: {error}", dir.display()))
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file())
+        .collect::<Vec<_>>();
+    paths.sort();
+
+    let names = crate::walk::links::<T>()
+        .into_iter()
+        .map(|link| link.name)
+        .collect::<Vec<_>>();
+    let mut detected = Vec::new();
+    let mut failures = Vec::new();
+    for path in paths {
+        let input = match std::fs::read_to_string(&path) {
+            Ok(input) => input,
+            Err(error) => {
+                failures.push(format!("{}: could not read: {error}", path.display()));
+                continue;
+            }
+        };
+
+        match T::try_from_str_migrations_with_version(&input) {
+            None => failures.push(format!("{}: no version matches", path.display())),
+            Some((version, Err(error))) => failures.push(format!(
+                "{}: detected as `{version}` but could not migrate: {error}",
+                path.display()
+            )),
+            Some((version, Ok(_))) => {
+                if let Some(expected) = expected_version(&path, &names) {
+                    if expected != version {
+                        failures.push(format!(
+                            "{}: expected `{expected}` but detected as `{version}`",
+                            path.display()
+                        ));
+                    }
+                }
+                detected.push((path, version));
+            }
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "Fixtures in {} failed to migrate to `{}`:\n{}\n\nPassed:\n{}",
+        dir.display(),
+        T::version_name(),
+        failures.join("\n"),
+        detected
+            .iter()
+            .map(|(path, version)| format!("{}: `{version}`", path.display()))
+            .collect::<Vec<_>>()
+            .join("\n")
+    );
+    detected
+}
+
+/// Assert that `sample` is detected as its own version in the chain of `T` with every
+/// combination of its top-level `Option` fields left out, returns how many payloads were
+/// checked
+///
+/// Guards against ABA: a payload written by `V` that is missing optional fields must not
+/// deserialize into a newer struct first. Give every optional field of `sample` a value,
+/// `serialize` receives it with the fields of each combination left out. The number of
+/// payloads doubles with each optional field.
+///
+/// # Panics
+///
+/// When `V` is not in the chain of `T`, or any payload fails to serialize or is detected as
+/// another version. Every failure is listed in the message.
+///
+/// ```rust
+/// use magic_migrate::TryMigrate;
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Deserialize, Serialize, Debug)]
+/// #[serde(deny_unknown_fields)]
+/// struct PersonV1 {
+///     name: String,
+///     title: Option<String>,
+///     team: Option<String>,
+/// }
+///
+/// #[derive(Deserialize, Serialize, Debug)]
+/// #[serde(deny_unknown_fields)]
+/// struct PersonV2 {
+///     name: String,
+///     job_title: Option<String>,
+/// }
+///
+/// impl From<PersonV1> for PersonV2 {
+///     fn from(value: PersonV1) -> Self {
+///         PersonV2 { name: value.name, job_title: value.title }
+///     }
+/// }
+///
+/// #[derive(Debug, thiserror::Error)]
+/// enum PersonError {}
+///
+/// magic_migrate::try_migrate_toml_chain!(error: PersonError, chain: [PersonV1, PersonV2]);
+///
+/// let sample = PersonV2 { name: "Schneems".to_string(), job_title: Some("Dev".to_string()) };
+/// let checked =
+///     magic_migrate::assert_optional_fields_unambiguous::<PersonV2, _, _, _>(&sample, |payload| {
+///         toml::to_string(payload)
+///     });
+/// assert_eq!(checked, 2);
+///
+/// // Without `title` and `team` a PersonV1 payload also deserializes as PersonV2
+/// let sample = PersonV1 {
+///     name: "Schneems".to_string(),
+///     title: Some("Dev".to_string()),
+///     team: Some("Languages".to_string()),
+/// };
+/// let result = std::panic::catch_unwind(|| {
+///     magic_migrate::assert_optional_fields_unambiguous::<PersonV2, _, _, _>(&sample, |payload| {
+///         toml::to_string(payload)
+///     })
+/// });
+/// assert!(result.is_err());
+/// ```
+pub fn assert_optional_fields_unambiguous<T, V, F, E>(sample: &V, serialize: F) -> usize
+where
+    T: TryMigrate,
+    V: TryMigrate + Serialize,
+    F: Fn(&Omitting<'_, V>) -> Result<String, E>,
+    E: Display,
+{
+    assert!(
+        crate::walk::contains::<T>(TypeId::of::<V>()),
+        "`{}` is not in the chain of `{}`",
+        V::version_name(),
+        T::version_name()
+    );
+
+    let optional = match Schema::of::<V>() {
+        Schema::Struct { fields, .. } => fields
+            .into_iter()
+            .filter(|(_, schema)| matches!(schema, Schema::Option(_)))
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>(),
+        _ => Vec::new(),
+    };
+
+    let mut failures = Vec::new();
+    let combinations = 1_usize << optional.len();
+    for mask in 0..combinations {
+        let omit = optional
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| mask & (1 << index) != 0)
+            .map(|(_, name)| *name)
+            .collect::<Vec<_>>();
+        let payload = Omitting::new(sample, omit);
+        let omitted = payload.omitted().join(", ");
+
+        match serialize(&payload) {
+            Err(error) => {
+                failures.push(format!("without [{omitted}]: could not serialize: {error}"))
+            }
+            Ok(input) => match T::plan(&input) {
+                Some(plan) if plan.matched == V::version_name() => {}
+                Some(plan) => failures.push(format!(
+                    "without [{omitted}]: detected as `{}`\n{input}",
+                    plan.matched
+                )),
+                None => failures.push(format!("without [{omitted}]: no version matches\n{input}")),
+            },
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "Payloads of `{}` with optional fields left out are not detected as it:\n{}",
+        V::version_name(),
+        failures.join("\n")
+    );
+    combinations
+}
+
+/// The version named at the start of the file name, if any
+fn expected_version(path: &Path, names: &[&'static str]) -> Option<&'static str> {
+    let file_name = path.file_name()?.to_str()?;
+    names.iter().copied().find(|name| {
+        file_name
+            .strip_prefix(name)
+            .and_then(|rest| rest.chars().next())
+            .is_some_and(|c| matches!(c, '.' | '_' | '-'))
+    })
+}
+
+/// The environment variable that makes [`assert_schema_snapshots`] overwrite snapshots
+/// that no longer match
+pub const UPDATE_SNAPSHOTS_ENV: &str = "MAGIC_MIGRATE_UPDATE_SNAPSHOTS";
+
+/// Assert that the [`TryMigrate::schema`] of every struct in the chain of `T` matches the
+/// snapshot stored for it in `dir`, returns the snapshots that were written
+///
+/// Once a version has shipped its fields must not change, otherwise stored data written by
+/// it no longer deserializes. Each struct's snapshot is kept in a
+/// `<`[`TryMigrate::version_name`]`>.txt` file (such as `tests/schema/PersonV1.txt`), commit
+/// them alongside the code. A missing snapshot is written, so adding a new version to the
+/// chain passes. To accept an intentional change, run the test with
+/// `MAGIC_MIGRATE_UPDATE_SNAPSHOTS=1` ([`UPDATE_SNAPSHOTS_ENV`]) and the mismatched snapshots
+/// are overwritten.
+///
+/// # Panics
+///
+/// When a snapshot can't be read or written, or doesn't match its struct. Every failure is
+/// listed in the message.
+///
+/// ```rust
+/// use magic_migrate::TryMigrate;
+#[doc = include_str!("fixtures/try_personV1_V2.txt")]
+///
+/// magic_migrate::try_migrate_toml_chain!(
+///     error: PersonMigrationError,
+///     chain: [PersonV1, PersonV2],
+/// );
+///
+/// let dir = std::env::temp_dir().join("magic_migrate_assert_schema_snapshots_doctest");
+/// let _ = std::fs::remove_dir_all(&dir);
+///
+/// // The first run records a snapshot for each version
+/// let written = magic_migrate::assert_schema_snapshots::<PersonV2>(&dir);
+/// assert_eq!(written.len(), 2);
+/// assert_eq!(
+///     std::fs::read_to_string(dir.join("PersonV1.txt")).unwrap(),
+///     "PersonV1 { name: str, title: option<str> }\n"
+/// );
+///
+/// // Later runs compare against them
+/// assert!(magic_migrate::assert_schema_snapshots::<PersonV2>(&dir).is_empty());
+///
+/// // Editing a version in place fails
+/// std::fs::write(dir.join("PersonV1.txt"), "PersonV1 { name: str }\n").unwrap();
+/// let result = std::panic::catch_unwind(|| {
+///     magic_migrate::assert_schema_snapshots::<PersonV2>(&dir)
+/// });
+/// assert!(result.is_err());
+/// ```
+pub fn assert_schema_snapshots<T: TryMigrate>(dir: impl AsRef<Path>) -> Vec<PathBuf> {
+    let dir = dir.as_ref();
+    let update = std::env::var_os(UPDATE_SNAPSHOTS_ENV).is_some_and(|value| !value.is_empty());
+    let mut schemas = Vec::new();
+    schemas_of::<T>(&mut schemas);
+
+    let mut written = Vec::new();
+    let mut failures = Vec::new();
+    for (name, schema) in schemas {
+        let path = dir.join(format!("{name}.txt"));
+        let snapshot = format!("{schema}\n");
+        let write = match std::fs::read_to_string(&path) {
+            Ok(stored) if stored == snapshot => false,
+            Ok(_) if update => true,
+            Ok(stored) => {
+                failures.push(format!(
+                    "{}: `{name}` changed without adding a new version\n  stored: {}\n  actual: {}",
+                    path.display(),
+                    stored.trim_end(),
+                    schema
+                ));
+                false
+            }
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => true,
+            Err(error) => {
+                failures.push(format!("{}: could not read: {error}", path.display()));
+                false
+            }
+        };
+
+        if write {
+            match std::fs::create_dir_all(dir).and_then(|()| std::fs::write(&path, snapshot)) {
+                Ok(()) => written.push(path),
+                Err(error) => {
+                    failures.push(format!("{}: could not write: {error}", path.display()))
+                }
+            }
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "Schema snapshots in {} don't match the chain of `{}`, set {UPDATE_SNAPSHOTS_ENV}=1 to overwrite them:\n{}",
+        dir.display(),
+        T::version_name(),
+        failures.join("\n")
+    );
+    written
+}
+
+/// The name and schema of every struct in the chain of `T`, oldest first
+fn schemas_of<T: TryMigrate>(out: &mut Vec<(&'static str, Schema)>) {
+    if TypeId::of::<T>() != TypeId::of::<T::TryFrom>() {
+        schemas_of::<T::TryFrom>(out);
+    }
+    out.push((T::version_name(), T::schema()));
+}
+
+/// Assert that no payload written by an older struct in the chain of `T` can also
+/// deserialize into a newer one, returns how many pairs of structs were checked
+///
+/// Guards against ABA without any input: the fields recorded by [`TryMigrate::schema`] are
+/// compared for every older and newer pair. A pair overlaps when the required fields of
+/// both are fields of both, with the same types, so a payload with only those fields is read
+/// as the newer struct. `Option` fields are optional. This assumes structs use
+/// `#[serde(deny_unknown_fields)]`, without it more payloads overlap than are reported. Use
+/// [`assert_optional_fields_unambiguous`] to check real payloads.
+///
+/// # Panics
+///
+/// When any pair overlaps. Every overlapping pair is listed in the message.
+///
+/// ```rust
+/// use magic_migrate::TryMigrate;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize, Debug)]
+/// #[serde(deny_unknown_fields)]
+/// struct PersonV1 {
+///     name: String,
+///     title: Option<String>,
+/// }
+///
+/// #[derive(Deserialize, Debug)]
+/// #[serde(deny_unknown_fields)]
+/// struct PersonV2 {
+///     name: String,
+///     job_title: String,
+/// }
+///
+/// #[derive(Deserialize, Debug)]
+/// #[serde(deny_unknown_fields)]
+/// struct PersonV3 {
+///     name: String,
+///     job_title: Option<String>,
+/// }
+///
+/// impl From<PersonV1> for PersonV2 {
+///     fn from(value: PersonV1) -> Self {
+///         PersonV2 { name: value.name, job_title: value.title.unwrap_or_default() }
+///     }
+/// }
+///
+/// impl From<PersonV2> for PersonV3 {
+///     fn from(value: PersonV2) -> Self {
+///         PersonV3 { name: value.name, job_title: Some(value.job_title) }
+///     }
+/// }
+///
+/// magic_migrate::try_migrate_toml_chain!(chain: [PersonV1, PersonV2, PersonV3]);
+///
+/// assert_eq!(magic_migrate::assert_versions_distinguishable::<PersonV2>(), 1);
+///
+/// // `name = 'Schneems'` is a PersonV1 payload that deserializes as PersonV3
+/// let result = std::panic::catch_unwind(|| {
+///     magic_migrate::assert_versions_distinguishable::<PersonV3>()
+/// });
+/// assert!(result.is_err());
+/// ```
+pub fn assert_versions_distinguishable<T: TryMigrate>() -> usize {
+    let mut schemas = Vec::new();
+    schemas_of::<T>(&mut schemas);
+
+    let mut checked = 0;
+    let mut failures = Vec::new();
+    for (index, (older, older_schema)) in schemas.iter().enumerate() {
+        for (newer, newer_schema) in &schemas[index + 1..] {
+            checked += 1;
+            if let Some(fields) = overlap(older_schema, newer_schema) {
+                failures.push(format!(
+                    "`{older}` payloads with only [{}] deserialize as `{newer}`",
+                    fields.join(", ")
+                ));
+            }
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "Structs in the chain of `{}` overlap:\n{}",
+        T::version_name(),
+        failures.join("\n")
+    );
+    checked
+}
+
+/// The fields of a payload that both structs deserialize, if there is one
+fn overlap(older: &Schema, newer: &Schema) -> Option<Vec<&'static str>> {
+    let (Schema::Struct { fields: older, .. }, Schema::Struct { fields: newer, .. }) =
+        (older, newer)
+    else {
+        return None;
+    };
+
+    let mut payload = older
+        .iter()
+        .chain(newer)
+        .filter(|(_, schema)| !matches!(schema, Schema::Option(_)))
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>();
+    payload.sort_unstable();
+    payload.dedup();
+
+    payload
+        .iter()
+        .all(|name| {
+            let older = older.iter().find(|(field, _)| field == name);
+            let newer = newer.iter().find(|(field, _)| field == name);
+            match (older, newer) {
+                (Some((_, older)), Some((_, newer))) => same_type(older, newer),
+                _ => false,
+            }
+        })
+        .then_some(payload)
+}
+
+/// True when a value of one field type can be read as the other, ignoring `Option`
+fn same_type(a: &Schema, b: &Schema) -> bool {
+    fn unwrap(schema: &Schema) -> &Schema {
+        match schema {
+            Schema::Option(inner) => inner,
+            other => other,
+        }
+    }
+    let (a, b) = (unwrap(a), unwrap(b));
+    a == b || *a == Schema::Unknown || *b == Schema::Unknown
+}
@@ -0,0 +1,259 @@
+//! The chain walk shared by the [`crate::TryMigrate`] associated functions
+
+use crate::{events, warnings, Format, MigrateError, TryMigrate};
+use std::any::{Any, TypeId};
+use std::panic::AssertUnwindSafe;
+
+/// The struct in the chain that the input was deserialized into
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Matched {
+    pub(crate) name: &'static str,
+    pub(crate) since: Option<&'static str>,
+    pub(crate) help: Option<&'static str>,
+    pub(crate) type_id: TypeId,
+    /// Deserialize the input into the matched struct again, the original is consumed by the migration
+    pub(crate) deserialize: fn(&str) -> Option<Box<dyn Any>>,
+}
+
+impl Matched {
+    fn of<T: TryMigrate>() -> Self {
+        Matched {
+            name: T::version_name(),
+            since: T::since(),
+            help: T::help(),
+            type_id: TypeId::of::<T>(),
+            deserialize: |input| {
+                T::deserialize(T::deserializer(input))
+                    .ok()
+                    .map(|value| Box::new(value) as Box<dyn Any>)
+            },
+        }
+    }
+}
+
+/// Try deserializing `input` into `T` then each earlier struct in the chain, migrating the
+/// first match forward to `T`
+pub(crate) fn walk<T: TryMigrate>(
+    input: &str,
+) -> Option<(Matched, Result<T, <T as TryMigrate>::Error>)> {
+    walk_skipping::<T>(input, 0)
+}
+
+/// Same as [`walk`] but ignores the first `skip` structs that match, used to retry with
+/// an older struct when migrating a newer match failed
+pub(crate) fn walk_skipping<T: TryMigrate>(
+    input: &str,
+    skip: usize,
+) -> Option<(Matched, Result<T, <T as TryMigrate>::Error>)> {
+    let attempt = T::deserialize(T::deserializer(input));
+    events::attempt(T::version_name(), attempt.is_ok());
+    let skip = match attempt {
+        Ok(instance) if skip == 0 => return Some((Matched::of::<T>(), Ok(instance))),
+        Ok(_) => skip - 1,
+        Err(_) => skip,
+    };
+
+    if TypeId::of::<T>() == TypeId::of::<T::TryFrom>() {
+        None
+    } else {
+        walk_skipping::<T::TryFrom>(input, skip).map(|(matched, inner)| {
+            let result = inner.map_err(Into::into).and_then(step::<T>);
+            (matched, result)
+        })
+    }
+}
+
+/// Same as [`walk`] but only tries the newest `limit` structs, starting with `T`
+pub(crate) fn walk_within<T: TryMigrate>(
+    input: &str,
+    limit: usize,
+) -> Option<(Matched, Result<T, <T as TryMigrate>::Error>)> {
+    if limit == 0 {
+        return None;
+    }
+
+    let attempt = T::deserialize(T::deserializer(input));
+    events::attempt(T::version_name(), attempt.is_ok());
+    if let Ok(instance) = attempt {
+        Some((Matched::of::<T>(), Ok(instance)))
+    } else if TypeId::of::<T>() == TypeId::of::<T::TryFrom>() {
+        None
+    } else {
+        walk_within::<T::TryFrom>(input, limit - 1).map(|(matched, inner)| {
+            let result = inner.map_err(Into::into).and_then(step::<T>);
+            (matched, result)
+        })
+    }
+}
+
+/// Same as [`walk`] but only tries deserializing into the first struct where `is_target`
+/// returns true, returns `None` when there is no such struct in the chain or it does not match
+pub(crate) fn walk_to<T: TryMigrate>(
+    input: &str,
+    is_target: &dyn Fn(&Matched) -> bool,
+) -> Option<(Matched, Result<T, <T as TryMigrate>::Error>)> {
+    let matched = Matched::of::<T>();
+    if is_target(&matched) {
+        let attempt = T::deserialize(T::deserializer(input)).ok();
+        events::attempt(T::version_name(), attempt.is_some());
+        attempt.map(|instance| (matched, Ok(instance)))
+    } else if TypeId::of::<T>() == TypeId::of::<T::TryFrom>() {
+        None
+    } else {
+        walk_to::<T::TryFrom>(input, is_target).map(|(matched, inner)| {
+            let result = inner.map_err(Into::into).and_then(step::<T>);
+            (matched, result)
+        })
+    }
+}
+
+/// Migrate `value`, a struct of type `id`, forward to `T`, returns `None` when `id` is not
+/// in the chain of `T`
+pub(crate) fn walk_from<T: TryMigrate>(
+    value: Box<dyn Any>,
+    id: TypeId,
+) -> Option<Result<T, <T as TryMigrate>::Error>> {
+    if TypeId::of::<T>() == id {
+        let value = value
+            .downcast::<T>()
+            .expect("The value is a T when its type id matches");
+        Some(Ok(*value))
+    } else if TypeId::of::<T>() == TypeId::of::<T::TryFrom>() {
+        None
+    } else {
+        walk_from::<T::TryFrom>(value, id)
+            .map(|inner| inner.map_err(Into::into).and_then(step::<T>))
+    }
+}
+
+/// The type of the newest struct in the chain of `T` that deserializes `input`, up to
+/// `parallelism` structs are tried at the same time on scoped threads
+pub(crate) fn newest_match_concurrent<T: TryMigrate>(
+    input: &str,
+    parallelism: usize,
+) -> Option<TypeId> {
+    let mut links = links::<T>();
+    links.reverse();
+    links.chunks(parallelism.max(1)).find_map(|batch| {
+        let matches = std::thread::scope(|scope| {
+            batch
+                .iter()
+                .map(|link| scope.spawn(|| (link.deserialize)(input).is_some()))
+                .collect::<Vec<_>>()
+                .into_iter()
+                .map(|handle| handle.join().unwrap_or(false))
+                .collect::<Vec<_>>()
+        });
+        batch
+            .iter()
+            .zip(matches)
+            .find(|(_, matched)| *matched)
+            .map(|(link, _)| link.type_id)
+    })
+}
+
+/// The result of [`walk_catching`]
+type Caught<T> = Result<T, MigrateError<<T as TryMigrate>::Error>>;
+
+/// Same as [`walk`] but a panic in a conversion step is returned as
+/// [`MigrateError::StepPanicked`] instead of unwinding into the caller
+pub(crate) fn walk_catching<T: TryMigrate>(input: &str) -> Option<(Matched, Caught<T>)> {
+    let attempt = T::deserialize(T::deserializer(input));
+    events::attempt(T::version_name(), attempt.is_ok());
+    if let Ok(instance) = attempt {
+        Some((Matched::of::<T>(), Ok(instance)))
+    } else if TypeId::of::<T>() == TypeId::of::<T::TryFrom>() {
+        None
+    } else {
+        walk_catching::<T::TryFrom>(input).map(|(matched, inner)| {
+            let result = inner
+                .map_err(|error| error.map_error(Into::into))
+                .and_then(|before| {
+                    match std::panic::catch_unwind(AssertUnwindSafe(|| step::<T>(before))) {
+                        Ok(result) => result.map_err(|error| MigrateError::Migrate {
+                            version: matched.name,
+                            error,
+                            help: matched.help,
+                        }),
+                        Err(payload) => Err(MigrateError::StepPanicked {
+                            version: T::version_name(),
+                            message: panic_message(payload.as_ref()),
+                        }),
+                    }
+                });
+            (matched, result)
+        })
+    }
+}
+
+/// The message passed to `panic!`, payloads that aren't a string are described instead
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(ToString::to_string)
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "panic payload is not a string".to_string())
+}
+
+/// Convert the struct before `T` in the chain into `T`
+pub(crate) fn step<T: TryMigrate>(
+    before: <T as TryMigrate>::TryFrom,
+) -> Result<T, <T as TryMigrate>::Error> {
+    let result = warnings::step(T::version_name(), || T::try_from(before));
+    events::step::<T>(&result);
+    result.map_err(Into::into)
+}
+
+/// The comment that `TryMigrate::to_toml_string_hinted` writes before the data
+pub(crate) const HINT_PREFIX: &str = "# magic_migrate: ";
+
+/// The version name from a leading [`HINT_PREFIX`] comment
+pub(crate) fn hint(input: &str) -> Option<&str> {
+    input
+        .lines()
+        .next()?
+        .strip_prefix(HINT_PREFIX)
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+}
+
+/// True when `id` is `T` or any struct before it in the chain
+pub(crate) fn contains<T: TryMigrate>(id: TypeId) -> bool {
+    if TypeId::of::<T>() == id {
+        true
+    } else if TypeId::of::<T>() == TypeId::of::<T::TryFrom>() {
+        false
+    } else {
+        contains::<T::TryFrom>(id)
+    }
+}
+
+/// Every struct in the chain of `T`, oldest first
+pub(crate) fn links<T: TryMigrate>() -> Vec<Matched> {
+    let mut links = if TypeId::of::<T>() == TypeId::of::<T::TryFrom>() {
+        Vec::new()
+    } else {
+        links::<T::TryFrom>()
+    };
+    links.push(Matched::of::<T>());
+    links
+}
+
+/// Same as [`walk`] but every struct is deserialized with `F` instead of
+/// [`TryMigrate::deserializer`], returns the type of the struct that matched
+pub(crate) fn walk_as<T: TryMigrate, F: Format>(
+    input: &str,
+) -> Option<(TypeId, Result<T, <T as TryMigrate>::Error>)> {
+    let attempt = T::deserialize(F::deserializer(input));
+    events::attempt(T::version_name(), attempt.is_ok());
+    if let Ok(instance) = attempt {
+        Some((TypeId::of::<T>(), Ok(instance)))
+    } else if TypeId::of::<T>() == TypeId::of::<T::TryFrom>() {
+        None
+    } else {
+        walk_as::<T::TryFrom, F>(input).map(|(type_id, inner)| {
+            let result = inner.map_err(Into::into).and_then(step::<T>);
+            (type_id, result)
+        })
+    }
+}
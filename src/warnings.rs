@@ -0,0 +1,88 @@
+//! Warnings emitted by conversion steps that succeed but lose data
+
+use crate::TryMigrate;
+use std::cell::{Cell, RefCell};
+use std::fmt::Display;
+
+/// The result of [`crate::TryMigrate::try_from_str_migrations_with_warnings`]
+pub(crate) type Warned<T> = Result<(T, Vec<MigrationWarning>), <T as TryMigrate>::Error>;
+
+thread_local! {
+    /// Warnings emitted since [`collect`] started, `None` when nothing is collecting
+    static COLLECTED: RefCell<Option<Vec<MigrationWarning>>> = const { RefCell::new(None) };
+    /// The [`crate::TryMigrate::version_name`] of the struct being converted into
+    static STEP: Cell<Option<&'static str>> = const { Cell::new(None) };
+}
+
+/// A conversion step dropped or coerced data but still succeeded
+///
+/// Emit one from a `TryFrom` impl with [`MigrationWarning::emit`], they are returned by
+/// [`crate::TryMigrate::try_from_str_migrations_with_warnings`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct MigrationWarning {
+    /// The [`crate::TryMigrate::version_name`] of the struct the step converted into
+    pub version: &'static str,
+    /// What was lost
+    pub message: String,
+}
+
+impl MigrationWarning {
+    /// Record a warning for the conversion step that is running
+    ///
+    /// Call it from a `TryFrom` impl in the chain. It does nothing unless the migration was
+    /// started with [`crate::TryMigrate::try_from_str_migrations_with_warnings`], or when
+    /// called outside of a conversion step.
+    pub fn emit(message: impl Into<String>) {
+        let Some(version) = STEP.get() else {
+            return;
+        };
+        COLLECTED.with_borrow_mut(|collected| {
+            if let Some(warnings) = collected {
+                warnings.push(MigrationWarning {
+                    version,
+                    message: message.into(),
+                });
+            }
+        });
+    }
+}
+
+impl Display for MigrationWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "`{}`: {}", self.version, self.message)
+    }
+}
+
+/// Run `f` and return the warnings emitted while it ran, oldest step first
+pub(crate) fn collect<R>(f: impl FnOnce() -> R) -> (R, Vec<MigrationWarning>) {
+    let restore = RestoreCollected(COLLECTED.replace(Some(Vec::new())));
+    let result = f();
+    let warnings = COLLECTED.take().unwrap_or_default();
+    drop(restore);
+    (result, warnings)
+}
+
+/// Run the conversion step into the struct named `version`
+pub(crate) fn step<R>(version: &'static str, f: impl FnOnce() -> R) -> R {
+    let _restore = Restore(STEP.replace(Some(version)));
+    f()
+}
+
+/// Puts back the step that was running when dropped, even if the step panicked
+struct Restore(Option<&'static str>);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        STEP.set(self.0);
+    }
+}
+
+/// Puts back the warnings of an outer [`collect`] when dropped, even if `f` panicked
+struct RestoreCollected(Option<Vec<MigrationWarning>>);
+
+impl Drop for RestoreCollected {
+    fn drop(&mut self) {
+        COLLECTED.set(self.0.take());
+    }
+}
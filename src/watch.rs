@@ -0,0 +1,136 @@
+//! Reload a file through a migration chain when it changes
+
+use crate::schema::{fnv1a, FNV_OFFSET};
+use crate::{load_config, LoadError, TryMigrate};
+use std::fmt::Debug;
+use std::path::PathBuf;
+use std::sync::mpsc::{Receiver, RecvTimeoutError, Sender};
+use std::thread::JoinHandle;
+use std::time::{Duration, SystemTime};
+
+type Loaded<T> = Result<T, LoadError<<T as TryMigrate>::Error>>;
+
+/// Watches a file and loads it with [`load_config`] each time it changes
+///
+/// The file is loaded once when the watch starts and again every time its modified time,
+/// length, or contents change, checked every `interval`. Each result is sent through a
+/// channel, read it with [`ConfigWatch::recv_timeout`] or [`ConfigWatch::receiver`]. A file
+/// that can't be read is sent as an error and retried on the next change. Polling stops
+/// when the watch is dropped.
+///
+/// Enabled with the `watch` feature.
+///
+/// ```rust
+/// use magic_migrate::{ConfigWatch, TryMigrate};
+/// use std::time::Duration;
+#[doc = include_str!("fixtures/try_personV1_V2.txt")]
+///
+/// magic_migrate::try_migrate_toml_chain!(
+///     error: PersonMigrationError,
+///     chain: [PersonV1, PersonV2],
+/// );
+///
+/// let dir = std::env::temp_dir().join("magic_migrate_config_watch_doctest");
+/// std::fs::create_dir_all(&dir).unwrap();
+/// let path = dir.join("person.toml");
+/// std::fs::write(&path, "name = 'Schneems'\ntitle = 'Dev'").unwrap();
+///
+/// let watch = ConfigWatch::<PersonV2>::new(&path, Duration::from_millis(10));
+/// let person = watch.recv_timeout(Duration::from_secs(5)).unwrap().unwrap();
+/// assert_eq!(person.job_title, "Dev".to_string());
+///
+/// // Same length, may have the same modified time
+/// std::fs::write(&path, "name = 'Schneems'\ntitle = 'Ops'").unwrap();
+/// let person = watch.recv_timeout(Duration::from_secs(5)).unwrap().unwrap();
+/// assert_eq!(person.job_title, "Ops".to_string());
+/// ```
+pub struct ConfigWatch<T: TryMigrate> {
+    path: PathBuf,
+    receiver: Receiver<Loaded<T>>,
+    /// Sending or dropping wakes the polling thread to stop it
+    stop: Sender<()>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl<T> ConfigWatch<T>
+where
+    T: TryMigrate + Send + 'static,
+    <T as TryMigrate>::Error: Send,
+{
+    /// Start polling `path` on a background thread
+    pub fn new(path: impl Into<PathBuf>, interval: Duration) -> Self {
+        let path = path.into();
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let (stop, stopped) = std::sync::mpsc::channel();
+
+        let thread = {
+            let path = path.clone();
+            std::thread::spawn(move || {
+                let mut last = None;
+                loop {
+                    let current = stamp(&path);
+                    if last.as_ref() != Some(&current) {
+                        last = Some(current);
+                        if sender.send(load_config::<T>(&path)).is_err() {
+                            break;
+                        }
+                    }
+                    if stopped.recv_timeout(interval) != Err(RecvTimeoutError::Timeout) {
+                        break;
+                    }
+                }
+            })
+        };
+
+        Self {
+            path,
+            receiver,
+            stop,
+            thread: Some(thread),
+        }
+    }
+}
+
+impl<T: TryMigrate> ConfigWatch<T> {
+    /// Wait up to `timeout` for the next time the file is loaded
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<Loaded<T>, RecvTimeoutError> {
+        self.receiver.recv_timeout(timeout)
+    }
+
+    /// The channel that each load is sent through
+    pub fn receiver(&self) -> &Receiver<Loaded<T>> {
+        &self.receiver
+    }
+}
+
+impl<T: TryMigrate> Drop for ConfigWatch<T> {
+    fn drop(&mut self) {
+        let _ = self.stop.send(());
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl<T: TryMigrate> Debug for ConfigWatch<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConfigWatch")
+            .field("path", &self.path)
+            .field("latest", &T::version_name())
+            .finish()
+    }
+}
+
+/// What's compared to tell if the file changed, `None` when it can't be read
+///
+/// The hash of the contents catches edits that keep the length within the granularity of
+/// the modified time.
+fn stamp(path: &std::path::Path) -> Option<(SystemTime, u64, u64)> {
+    let metadata = std::fs::metadata(path).ok()?;
+    let contents = std::fs::read(path).ok()?;
+    Some((
+        metadata.modified().ok()?,
+        metadata.len(),
+        fnv1a(FNV_OFFSET, &contents),
+    ))
+}